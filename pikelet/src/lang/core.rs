@@ -3,11 +3,12 @@
 //! This is not intended to be used directly by users of the programming
 //! language.
 
-use fxhash::FxHashMap;
+use fxhash::{FxHashMap, FxHashSet};
 use std::fmt;
+use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 
-use crate::lang::Located;
+use crate::lang::{Located, Location};
 
 pub mod marshall;
 pub mod semantics;
@@ -107,22 +108,407 @@ pub enum TermData {
     Error,
 }
 
+impl Constant {
+    /// Compare two constants, treating floating point numbers as equal if
+    /// they have the same bit pattern.
+    fn eq_bitwise(&self, other: &Constant) -> bool {
+        match (self, other) {
+            (Constant::F32(value0), Constant::F32(value1)) => value0.to_bits() == value1.to_bits(),
+            (Constant::F64(value0), Constant::F64(value1)) => value0.to_bits() == value1.to_bits(),
+            (constant0, constant1) => constant0 == constant1,
+        }
+    }
+
+    /// Hash a constant, consistently with [`Constant::eq_bitwise`].
+    fn hash_bitwise<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Constant::U8(value) => value.hash(state),
+            Constant::U16(value) => value.hash(state),
+            Constant::U32(value) => value.hash(state),
+            Constant::U64(value) => value.hash(state),
+            Constant::S8(value) => value.hash(state),
+            Constant::S16(value) => value.hash(state),
+            Constant::S32(value) => value.hash(state),
+            Constant::S64(value) => value.hash(state),
+            Constant::F32(value) => value.to_bits().hash(state),
+            Constant::F64(value) => value.to_bits().hash(state),
+            Constant::Char(value) => value.hash(state),
+            Constant::String(value) => value.hash(state),
+        }
+    }
+}
+
 impl From<Constant> for TermData {
     fn from(constant: Constant) -> TermData {
         TermData::Constant(constant)
     }
 }
 
+impl TermData {
+    /// Compare two terms for structural equality, ignoring the source
+    /// locations of their subterms.
+    pub fn eq_ignoring_locations(&self, other: &TermData) -> bool {
+        fn eq(term0: &Term, term1: &Term) -> bool {
+            term0.data.eq_ignoring_locations(&term1.data)
+        }
+
+        fn eq_all(terms0: &[Arc<Term>], terms1: &[Arc<Term>]) -> bool {
+            terms0.len() == terms1.len()
+                && Iterator::zip(terms0.iter(), terms1.iter()).all(|(t0, t1)| eq(t0, t1))
+        }
+
+        match (self, other) {
+            (TermData::Global(name0), TermData::Global(name1)) => name0 == name1,
+            (TermData::Local(index0), TermData::Local(index1)) => index0 == index1,
+
+            (TermData::Ann(term0, type0), TermData::Ann(term1, type1)) => {
+                eq(term0, term1) && eq(type0, type1)
+            }
+
+            (TermData::TypeType, TermData::TypeType) => true,
+
+            (
+                TermData::FunctionType(name0, input_type0, output_type0),
+                TermData::FunctionType(name1, input_type1, output_type1),
+            ) => name0 == name1 && eq(input_type0, input_type1) && eq(output_type0, output_type1),
+            (TermData::FunctionTerm(name0, output0), TermData::FunctionTerm(name1, output1)) => {
+                name0 == name1 && eq(output0, output1)
+            }
+            (TermData::FunctionElim(head0, input0), TermData::FunctionElim(head1, input1)) => {
+                eq(head0, head1) && eq(input0, input1)
+            }
+
+            (TermData::RecordType(labels0, types0), TermData::RecordType(labels1, types1)) => {
+                labels0 == labels1 && eq_all(types0, types1)
+            }
+            (TermData::RecordTerm(labels0, terms0), TermData::RecordTerm(labels1, terms1)) => {
+                labels0 == labels1 && eq_all(terms0, terms1)
+            }
+            (TermData::RecordElim(head0, label0), TermData::RecordElim(head1, label1)) => {
+                label0 == label1 && eq(head0, head1)
+            }
+
+            (TermData::ArrayTerm(terms0), TermData::ArrayTerm(terms1))
+            | (TermData::ListTerm(terms0), TermData::ListTerm(terms1)) => eq_all(terms0, terms1),
+
+            (TermData::Constant(constant0), TermData::Constant(constant1)) => {
+                constant0.eq_bitwise(constant1)
+            }
+
+            (TermData::Error, TermData::Error) => true,
+
+            (_, _) => false,
+        }
+    }
+
+    /// Hash a term, ignoring the source locations of its subterms. This is
+    /// consistent with [`TermData::eq_ignoring_locations`].
+    pub fn hash_ignoring_locations<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            TermData::Global(name) => name.hash(state),
+            TermData::Local(local_index) => local_index.0.hash(state),
+
+            TermData::Ann(term, r#type) => {
+                term.data.hash_ignoring_locations(state);
+                r#type.data.hash_ignoring_locations(state);
+            }
+
+            TermData::TypeType => {}
+
+            TermData::FunctionType(name, input_type, output_type) => {
+                name.hash(state);
+                input_type.data.hash_ignoring_locations(state);
+                output_type.data.hash_ignoring_locations(state);
+            }
+            TermData::FunctionTerm(name, output_term) => {
+                name.hash(state);
+                output_term.data.hash_ignoring_locations(state);
+            }
+            TermData::FunctionElim(head_term, input_term) => {
+                head_term.data.hash_ignoring_locations(state);
+                input_term.data.hash_ignoring_locations(state);
+            }
+
+            TermData::RecordType(labels, terms) | TermData::RecordTerm(labels, terms) => {
+                labels.hash(state);
+                terms.len().hash(state);
+                for term in terms.iter() {
+                    term.data.hash_ignoring_locations(state);
+                }
+            }
+            TermData::RecordElim(head_term, label) => {
+                head_term.data.hash_ignoring_locations(state);
+                label.hash(state);
+            }
+
+            TermData::ArrayTerm(terms) | TermData::ListTerm(terms) => {
+                terms.len().hash(state);
+                for term in terms {
+                    term.data.hash_ignoring_locations(state);
+                }
+            }
+
+            TermData::Constant(constant) => constant.hash_bitwise(state),
+
+            TermData::Error => {}
+        }
+    }
+}
+
+impl Term {
+    /// Apply a function to the locations of a term and each of its subterms.
+    pub fn map_locations(&self, f: impl Fn(Location) -> Location) -> Term {
+        fn go(term: &Term, f: &dyn Fn(Location) -> Location) -> Term {
+            let map = |term: &Arc<Term>| Arc::new(go(term, f));
+            let map_all = |terms: &[Arc<Term>]| terms.iter().map(map).collect::<Vec<_>>();
+
+            let term_data = match &term.data {
+                TermData::Global(name) => TermData::Global(name.clone()),
+                TermData::Local(local_index) => TermData::Local(*local_index),
+                TermData::Ann(term, r#type) => TermData::Ann(map(term), map(r#type)),
+                TermData::TypeType => TermData::TypeType,
+                TermData::FunctionType(name, input_type, output_type) => {
+                    TermData::FunctionType(name.clone(), map(input_type), map(output_type))
+                }
+                TermData::FunctionTerm(name, output_term) => {
+                    TermData::FunctionTerm(name.clone(), map(output_term))
+                }
+                TermData::FunctionElim(head_term, input_term) => {
+                    TermData::FunctionElim(map(head_term), map(input_term))
+                }
+                TermData::RecordType(labels, types) => {
+                    TermData::RecordType(labels.clone(), map_all(types).into())
+                }
+                TermData::RecordTerm(labels, terms) => {
+                    TermData::RecordTerm(labels.clone(), map_all(terms).into())
+                }
+                TermData::RecordElim(head_term, label) => {
+                    TermData::RecordElim(map(head_term), label.clone())
+                }
+                TermData::ArrayTerm(terms) => TermData::ArrayTerm(map_all(terms)),
+                TermData::ListTerm(terms) => TermData::ListTerm(map_all(terms)),
+                TermData::Constant(constant) => TermData::Constant(constant.clone()),
+                TermData::Error => TermData::Error,
+            };
+
+            Term::new(f(term.location), term_data)
+        }
+
+        go(self, &f)
+    }
+
+    /// Returns `true` if the term refers to the local variable bound at the
+    /// given index.
+    pub fn references_local(&self, local_index: LocalIndex) -> bool {
+        fn go(term: &Term, index: u32) -> bool {
+            match &term.data {
+                TermData::Local(local_index) => local_index.0 == index,
+                TermData::Ann(term, r#type) => go(term, index) || go(r#type, index),
+                TermData::FunctionType(_, input_type, output_type) => {
+                    go(input_type, index) || go(output_type, index + 1)
+                }
+                TermData::FunctionTerm(_, output_term) => go(output_term, index + 1),
+                TermData::FunctionElim(head_term, input_term) => {
+                    go(head_term, index) || go(input_term, index)
+                }
+                // Each entry is bound in the entries that follow it
+                TermData::RecordType(_, terms) | TermData::RecordTerm(_, terms) => {
+                    (terms.iter().zip(index..)).any(|(term, index)| go(term, index))
+                }
+                TermData::RecordElim(head_term, _) => go(head_term, index),
+                TermData::ArrayTerm(terms) | TermData::ListTerm(terms) => {
+                    terms.iter().any(|term| go(term, index))
+                }
+                TermData::Global(_)
+                | TermData::TypeType
+                | TermData::Constant(_)
+                | TermData::Error => false,
+            }
+        }
+
+        go(self, local_index.0)
+    }
+
+    /// Returns the first local variable in the term that would not be bound
+    /// in a local environment of the given size.
+    ///
+    /// This is useful for validating terms that were constructed by hand,
+    /// before passing them to the type checker.
+    pub fn find_unbound_local(&self, local_size: LocalSize) -> Option<&Term> {
+        fn go(term: &Term, local_size: LocalSize) -> Option<&Term> {
+            match &term.data {
+                TermData::Local(local_index) => match local_size.index_to_level(*local_index) {
+                    Some(_) => None,
+                    None => Some(term),
+                },
+                TermData::Ann(term, r#type) => {
+                    go(term, local_size).or_else(|| go(r#type, local_size))
+                }
+                TermData::FunctionType(_, input_type, output_type) => {
+                    go(input_type, local_size).or_else(|| go(output_type, local_size.increment()))
+                }
+                TermData::FunctionTerm(_, output_term) => go(output_term, local_size.increment()),
+                TermData::FunctionElim(head_term, input_term) => {
+                    go(head_term, local_size).or_else(|| go(input_term, local_size))
+                }
+                // Each entry is bound in the entries that follow it
+                TermData::RecordType(_, terms) | TermData::RecordTerm(_, terms) => {
+                    let mut local_size = local_size;
+                    for term in terms.iter() {
+                        if let Some(term) = go(term, local_size) {
+                            return Some(term);
+                        }
+                        local_size = local_size.increment();
+                    }
+                    None
+                }
+                TermData::RecordElim(head_term, _) => go(head_term, local_size),
+                TermData::ArrayTerm(terms) | TermData::ListTerm(terms) => {
+                    terms.iter().find_map(|term| go(term, local_size))
+                }
+                TermData::Global(_)
+                | TermData::TypeType
+                | TermData::Constant(_)
+                | TermData::Error => None,
+            }
+        }
+
+        go(self, local_size)
+    }
+
+    /// Returns the number of terms on the most deeply nested path through
+    /// this term. The heads of function eliminations are not counted as
+    /// nested, because long spines of eliminations, like `f a b c`, are
+    /// evaluated and read back without recursing on their heads.
+    ///
+    /// This is computed without recursion, so that it can be used to guard
+    /// recursive passes against overflowing the stack.
+    pub fn depth(&self) -> usize {
+        let mut max_depth = 0;
+        let mut pending_terms = vec![(self, 1)];
+
+        while let Some((term, depth)) = pending_terms.pop() {
+            max_depth = std::cmp::max(max_depth, depth);
+            match &term.data {
+                TermData::Ann(term, r#type) => {
+                    pending_terms.push((term, depth + 1));
+                    pending_terms.push((r#type, depth + 1));
+                }
+                TermData::FunctionType(_, input_type, output_type) => {
+                    pending_terms.push((input_type, depth + 1));
+                    pending_terms.push((output_type, depth + 1));
+                }
+                TermData::FunctionTerm(_, output_term) => {
+                    pending_terms.push((output_term, depth + 1));
+                }
+                TermData::FunctionElim(head_term, input_term) => {
+                    pending_terms.push((head_term, depth));
+                    pending_terms.push((input_term, depth + 1));
+                }
+                TermData::RecordType(_, terms) | TermData::RecordTerm(_, terms) => {
+                    pending_terms.extend(terms.iter().map(|term| (term.as_ref(), depth + 1)));
+                }
+                TermData::RecordElim(head_term, _) => pending_terms.push((head_term, depth + 1)),
+                TermData::ArrayTerm(terms) | TermData::ListTerm(terms) => {
+                    pending_terms.extend(terms.iter().map(|term| (term.as_ref(), depth + 1)));
+                }
+                TermData::Global(_)
+                | TermData::Local(_)
+                | TermData::TypeType
+                | TermData::Constant(_)
+                | TermData::Error => {}
+            }
+        }
+
+        max_depth
+    }
+
+    /// Replace the locations of a term and each of its subterms with
+    /// [`Location::Generated`].
+    pub fn clear_locations(&self) -> Term {
+        self.map_locations(|_| Location::generated())
+    }
+}
+
+/// Concisely display a term using the [core pretty printer], which is
+/// easier to read than its `Debug` output.
+///
+/// [core pretty printer]: crate::pass::core_to_pretty
+impl fmt::Display for Term {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pretty_alloc = pretty::BoxAllocator;
+        let doc = crate::pass::core_to_pretty::from_term(&pretty_alloc, self);
+        write!(f, "{}", doc.1.pretty(80))
+    }
+}
+
+/// A term that can be used as a key in hash maps and sets. Source locations
+/// are ignored when comparing and hashing the wrapped term.
+#[derive(Clone, Debug)]
+pub struct HashableTerm(pub Arc<Term>);
+
+impl PartialEq for HashableTerm {
+    fn eq(&self, other: &HashableTerm) -> bool {
+        self.0.data.eq_ignoring_locations(&other.0.data)
+    }
+}
+
+impl Eq for HashableTerm {}
+
+impl Hash for HashableTerm {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.data.hash_ignoring_locations(state);
+    }
+}
+
 /// An environment of global definitions.
 pub struct Globals {
     entries: FxHashMap<String, (Arc<Term>, Option<Arc<Term>>)>,
+    opaque_names: FxHashSet<String>,
+    /// Cached values of the global definitions, evaluated on first use.
+    definition_values: semantics::DefinitionValues,
 }
 
 impl Globals {
     pub fn new(entries: FxHashMap<String, (Arc<Term>, Option<Arc<Term>>)>) -> Globals {
-        Globals { entries }
+        let definition_values = semantics::DefinitionValues::new(
+            (entries.iter())
+                .filter(|(_, (_, definition))| definition.is_some())
+                .map(|(name, _)| name),
+        );
+
+        Globals {
+            entries,
+            opaque_names: FxHashSet::default(),
+            definition_values,
+        }
+    }
+
+    /// Set whether the definition of a global should be hidden during
+    /// evaluation. Opaque globals remain stuck, even if they have a
+    /// definition. Globals are transparent by default.
+    pub fn set_opaque(&mut self, name: &str, opaque: bool) {
+        match opaque {
+            true => self.opaque_names.insert(name.to_owned()),
+            false => self.opaque_names.remove(name),
+        };
+
+        // Cached definitions might have unfolded the global, so clear them
+        self.definition_values.clear();
+    }
+
+    /// Returns `true` if the definition of the global should be hidden during
+    /// evaluation.
+    pub fn is_opaque(&self, name: &str) -> bool {
+        self.opaque_names.contains(name)
     }
 
+    /// Lookup the type of a global, along with its definition if it has one.
+    ///
+    /// Globals with no definition, like primitive types, are returned with a
+    /// definition of `None`.
     pub fn get(&self, name: &str) -> Option<&(Arc<Term>, Option<Arc<Term>>)> {
         self.entries.get(name)
     }
@@ -162,6 +548,22 @@ impl Default for Globals {
         entries.insert("String".to_owned(), (global("Type"), None));
         entries.insert("true".to_owned(), (global("Bool"), None));
         entries.insert("false".to_owned(), (global("Bool"), None));
+        entries.insert("Never".to_owned(), (global("Type"), None));
+        entries.insert(
+            "absurd".to_owned(),
+            (
+                // Fun (A : Type) -> Never -> A
+                Arc::new(Term::generated(TermData::FunctionType(
+                    Some("A".to_owned()),
+                    type_type(),
+                    function_type(
+                        global("Never"),
+                        Arc::new(Term::generated(TermData::Local(LocalIndex(1)))),
+                    ),
+                ))),
+                None,
+            ),
+        );
         entries.insert(
             "Array".to_owned(),
             (
@@ -316,6 +718,20 @@ impl<Entry: Clone> Locals<Entry> {
             Some((local_index, entry))
         })
     }
+
+    /// Returns a reverse iterator over the entries that were pushed onto the
+    /// environment since it was the same as `other`, along with the indices
+    /// where those entries were bound.
+    ///
+    /// This assumes that `other` is a prefix of this environment, which is
+    /// useful for seeing what was bound while debugging.
+    pub fn diff<'a>(
+        &'a self,
+        other: &Locals<Entry>,
+    ) -> impl Iterator<Item = (LocalIndex, &'a Entry)> {
+        let count = self.entries.len().saturating_sub(other.entries.len());
+        self.iter_rev().take(count)
+    }
 }
 
 impl<Entry: Clone + fmt::Debug> fmt::Debug for Locals<Entry> {
@@ -325,3 +741,196 @@ impl<Entry: Clone + fmt::Debug> fmt::Debug for Locals<Entry> {
             .finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::FileId;
+
+    #[test]
+    fn get_global_with_definition() {
+        let globals = Globals::default();
+        let (r#type, definition) = globals.get("Type").unwrap();
+
+        assert!(matches!(r#type.data, TermData::TypeType));
+        assert!(matches!(
+            definition.as_ref().map(|term| &term.data),
+            Some(TermData::TypeType),
+        ));
+    }
+
+    #[test]
+    fn get_global_without_definition() {
+        let globals = Globals::default();
+        let (r#type, definition) = globals.get("true").unwrap();
+
+        assert!(matches!(&r#type.data, TermData::Global(name) if name == "Bool"));
+        assert!(definition.is_none());
+    }
+
+    #[test]
+    fn get_unknown_global() {
+        let globals = Globals::default();
+
+        assert!(globals.get("unknown").is_none());
+    }
+
+    #[test]
+    fn globals_are_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+
+        assert_send_sync::<Globals>();
+    }
+
+    fn hash(term: &HashableTerm) -> u64 {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        term.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn id_term(file_id: FileId, start: usize) -> HashableTerm {
+        let location =
+            |offset| Location::file_range(file_id, (start + offset)..(start + offset + 1));
+        let body = Term::new(location(1), TermData::Local(LocalIndex(0)));
+        let term = TermData::FunctionTerm("x".to_owned(), Arc::new(body));
+        HashableTerm(Arc::new(Term::new(location(0), term)))
+    }
+
+    #[test]
+    fn eq_and_hash_ignore_locations() {
+        let term0 = id_term(0, 0);
+        let term1 = id_term(1, 42);
+
+        assert_eq!(term0, term1);
+        assert_eq!(hash(&term0), hash(&term1));
+    }
+
+    #[test]
+    fn eq_distinguishes_terms() {
+        let term0 = HashableTerm(Arc::new(Term::generated(TermData::Global(
+            "true".to_owned(),
+        ))));
+        let term1 = HashableTerm(Arc::new(Term::generated(TermData::Global(
+            "false".to_owned(),
+        ))));
+
+        assert_ne!(term0, term1);
+    }
+
+    #[test]
+    fn eq_and_hash_nan_constants() {
+        let term0 = HashableTerm(Arc::new(Term::generated(TermData::from(Constant::F64(
+            f64::NAN,
+        )))));
+        let term1 = HashableTerm(Arc::new(Term::generated(TermData::from(Constant::F64(
+            f64::NAN,
+        )))));
+
+        assert_eq!(term0, term1);
+        assert_eq!(hash(&term0), hash(&term1));
+    }
+
+    #[test]
+    fn clear_locations() {
+        use std::cell::Cell;
+
+        let term = id_term(0, 3).0.clear_locations();
+
+        let file_range_count = Cell::new(0);
+        term.map_locations(|location| {
+            if let Location::FileRange(_, _) = location {
+                file_range_count.set(file_range_count.get() + 1);
+            }
+            location
+        });
+        assert_eq!(file_range_count.get(), 0);
+
+        let expected_term = Term::generated(TermData::FunctionTerm(
+            "x".to_owned(),
+            Arc::new(Term::generated(TermData::Local(LocalIndex(0)))),
+        ));
+        assert!(term.data.eq_ignoring_locations(&expected_term.data));
+    }
+
+    #[test]
+    fn map_locations() {
+        let term = id_term(0, 3).0.map_locations(|location| match location {
+            Location::FileRange(file_id, range) => {
+                Location::file_range(file_id + 1, (range.start + 10)..(range.end + 10))
+            }
+            Location::Generated => Location::Generated,
+        });
+
+        match (term.location, &term.data) {
+            (Location::FileRange(1, range), TermData::FunctionTerm(_, body)) => {
+                assert_eq!((range.start, range.end), (13, 14));
+                assert!(
+                    matches!(body.location, Location::FileRange(1, range) if range.start == 14)
+                );
+            }
+            _ => panic!("unexpected term: {:?}", term),
+        }
+    }
+
+    #[test]
+    fn diff_locals() {
+        let mut locals = Locals::new();
+        locals.push("x");
+        let original_locals = locals.clone();
+        locals.push("y");
+
+        let diff = locals.diff(&original_locals).collect::<Vec<_>>();
+        assert_eq!(diff, [(LocalIndex(0), &"y")]);
+        assert_eq!(original_locals.diff(&original_locals).count(), 0);
+    }
+
+    #[test]
+    fn display() {
+        // fun x => x : Type -> Type
+        let term = Term::generated(TermData::Ann(
+            Arc::new(Term::generated(TermData::FunctionTerm(
+                "x".to_owned(),
+                Arc::new(Term::generated(TermData::Local(LocalIndex(0)))),
+            ))),
+            Arc::new(Term::generated(TermData::FunctionType(
+                None,
+                Arc::new(Term::generated(TermData::TypeType)),
+                Arc::new(Term::generated(TermData::TypeType)),
+            ))),
+        ));
+
+        assert_eq!(term.to_string(), "fun _ => local 0 : Type -> Type");
+    }
+
+    #[test]
+    fn find_unbound_local() {
+        let local = |index| Arc::new(Term::generated(TermData::Local(LocalIndex(index))));
+        let empty_size = Locals::<()>::new().size();
+
+        // fun x => x
+        let term = Term::generated(TermData::FunctionTerm("x".to_owned(), local(0)));
+        assert!(term.find_unbound_local(empty_size).is_none());
+
+        // fun x => #1
+        let term = Term::generated(TermData::FunctionTerm("x".to_owned(), local(1)));
+        match term.find_unbound_local(empty_size) {
+            Some(term) => assert!(matches!(term.data, TermData::Local(LocalIndex(1)))),
+            None => panic!("expected an unbound local"),
+        }
+        assert!(term.find_unbound_local(empty_size.increment()).is_none());
+
+        // record { a = Type, b = a }
+        let term = Term::generated(TermData::RecordTerm(
+            vec!["a".to_owned(), "b".to_owned()].into(),
+            vec![Arc::new(Term::generated(TermData::TypeType)), local(0)].into(),
+        ));
+        assert!(term.find_unbound_local(empty_size).is_none());
+
+        // record { a = #0, b = Type }
+        let term = Term::generated(TermData::RecordTerm(
+            vec!["a".to_owned(), "b".to_owned()].into(),
+            vec![local(0), Arc::new(Term::generated(TermData::TypeType))].into(),
+        ));
+        assert!(term.find_unbound_local(empty_size).is_some());
+    }
+}
@@ -3,9 +3,9 @@
 //! [normalisation-by-evaluation]: https://en.wikipedia.org/wiki/Normalisation_by_evaluation
 
 use contracts::debug_ensures;
+use fxhash::FxHashMap;
 use once_cell::sync::OnceCell;
-use std::cell::RefCell;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use crate::lang::core::{Constant, Globals, LocalLevel, LocalSize, Locals, Term, TermData};
 
@@ -200,10 +200,10 @@ enum LazyInit {
 }
 
 /// A lazily initialized value.
-#[derive(Clone, Debug)]
+#[derive(Debug)]
 pub struct LazyValue {
     /// Initialization operation. Will be set to `None` if `cell` is forced.
-    init: RefCell<Option<LazyInit>>,
+    init: Mutex<Option<LazyInit>>,
     /// A once-cell to hold the lazily initialized value.
     cell: OnceCell<Arc<Value>>,
 }
@@ -212,7 +212,7 @@ impl LazyValue {
     /// Eagerly construct the lazy value.
     pub fn new(value: Arc<Value>) -> LazyValue {
         LazyValue {
-            init: RefCell::new(None),
+            init: Mutex::new(None),
             cell: OnceCell::from(value),
         }
     }
@@ -220,7 +220,7 @@ impl LazyValue {
     /// Lazily evaluate a term using the given universe offset and local values.
     pub fn eval(locals: Locals<Arc<Value>>, term: Arc<Term>) -> LazyValue {
         LazyValue {
-            init: RefCell::new(Some(LazyInit::EvalTerm(locals, term))),
+            init: Mutex::new(Some(LazyInit::EvalTerm(locals, term))),
             cell: OnceCell::new(),
         }
     }
@@ -228,26 +228,67 @@ impl LazyValue {
     /// Lazily apply an elimination.
     pub fn apply_elim(head: Arc<LazyValue>, elim: Elim) -> LazyValue {
         LazyValue {
-            init: RefCell::new(Some(LazyInit::ApplyElim(head, elim))),
+            init: Mutex::new(Some(LazyInit::ApplyElim(head, elim))),
             cell: OnceCell::new(),
         }
     }
 
     /// Force the evaluation of a lazy value.
     pub fn force(&self, globals: &Globals) -> &Arc<Value> {
-        self.cell.get_or_init(|| match self.init.replace(None) {
-            Some(LazyInit::EvalTerm(mut locals, term)) => eval(globals, &mut locals, &term),
-            Some(LazyInit::ApplyElim(head, Elim::Record(label))) => {
-                record_elim(globals, head.force(globals).clone(), &label)
-            }
-            Some(LazyInit::ApplyElim(head, Elim::Function(input))) => {
-                function_elim(globals, head.force(globals).clone(), input)
+        self.cell.get_or_init(|| {
+            let init = self.init.lock().unwrap().take();
+            match init {
+                Some(LazyInit::EvalTerm(mut locals, term)) => eval(globals, &mut locals, &term),
+                Some(LazyInit::ApplyElim(head, Elim::Record(label))) => {
+                    record_elim(globals, head.force(globals).clone(), &label)
+                }
+                Some(LazyInit::ApplyElim(head, Elim::Function(input))) => {
+                    function_elim(globals, head.force(globals).clone(), input)
+                }
+                None => panic!("Lazy instance has previously been poisoned"),
             }
-            None => panic!("Lazy instance has previously been poisoned"),
         })
     }
 }
 
+/// Cached values of the definitions of the [globals][Globals], evaluated on
+/// first use.
+pub struct DefinitionValues {
+    values: FxHashMap<String, OnceCell<Arc<LazyValue>>>,
+}
+
+impl DefinitionValues {
+    /// Create an empty cache for the definitions with the given names.
+    pub fn new<'a>(names: impl IntoIterator<Item = &'a String>) -> DefinitionValues {
+        DefinitionValues {
+            values: (names.into_iter())
+                .map(|name| (name.clone(), OnceCell::new()))
+                .collect(),
+        }
+    }
+
+    /// Clear the cached values, so that they are evaluated again on next use.
+    pub fn clear(&mut self) {
+        for value in self.values.values_mut() {
+            value.take();
+        }
+    }
+}
+
+/// Lookup the lazily evaluated definition of a global, if it has one.
+///
+/// The value is cached, so the definition is evaluated at most once, no
+/// matter how many times the global is referenced.
+fn definition_value(globals: &Globals, name: &str) -> Option<Arc<LazyValue>> {
+    let definition = globals.get(name)?.1.as_ref()?;
+    let value = globals.definition_values.values.get(name)?.get_or_init(|| {
+        let locals = Locals::new();
+        Arc::new(LazyValue::eval(locals, definition.clone()))
+    });
+
+    Some(value.clone())
+}
+
 /// Fully normalize a [`Term`] using [normalization by evaluation].
 ///
 /// [`Term`]: crate::lang::core::Term
@@ -265,17 +306,15 @@ pub fn normalize(globals: &Globals, locals: &mut Locals<Arc<Value>>, term: &Term
 #[debug_ensures(locals.size() == old(locals.size()))]
 pub fn eval(globals: &Globals, locals: &mut Locals<Arc<Value>>, term: &Term) -> Arc<Value> {
     match &term.data {
-        TermData::Global(name) => match globals.get(name) {
-            Some((_, Some(term))) => {
-                let head = Head::Global(name.into());
-                let value = LazyValue::eval(locals.clone(), term.clone());
-                Arc::new(Value::Unstuck(head, Vec::new(), Arc::new(value)))
-            }
-            Some((_, None)) | None => {
-                let head = Head::Global(name.into());
-                Arc::new(Value::Stuck(head, Vec::new()))
+        TermData::Global(name) => {
+            let head = Head::Global(name.into());
+            match definition_value(globals, name) {
+                Some(value) if !globals.is_opaque(name) => {
+                    Arc::new(Value::Unstuck(head, Vec::new(), value))
+                }
+                Some(_) | None => Arc::new(Value::Stuck(head, Vec::new())),
             }
-        },
+        }
         TermData::Local(local_index) => match locals.get(*local_index) {
             Some(value) => value.clone(),
             // FIXME: Local gluing is kind of broken right now :(
@@ -319,9 +358,20 @@ pub fn eval(globals: &Globals, locals: &mut Locals<Arc<Value>>, term: &Term) ->
             FunctionClosure::new(locals.clone(), output_term.clone()),
         )),
         TermData::FunctionElim(head, input) => {
+            // Collect the inputs of the spine iteratively, to avoid recursing
+            // deeply when evaluating long chains of eliminations, eg. `f a b c`.
+            let mut head = head;
+            let mut inputs = vec![input];
+            while let TermData::FunctionElim(next_head, input) = &head.data {
+                head = next_head;
+                inputs.push(input);
+            }
+
             let head = eval(globals, locals, head);
-            let input = LazyValue::eval(locals.clone(), input.clone());
-            function_elim(globals, head, Arc::new(input))
+            inputs.into_iter().rev().fold(head, |head, input| {
+                let input = LazyValue::eval(locals.clone(), input.clone());
+                function_elim(globals, head, Arc::new(input))
+            })
         }
 
         TermData::ArrayTerm(term_entries) => {
@@ -347,6 +397,33 @@ pub fn eval(globals: &Globals, locals: &mut Locals<Arc<Value>>, term: &Term) ->
     }
 }
 
+/// Return the names and types of the inputs that a function of the given
+/// type is yet to be applied to.
+///
+/// The types of later inputs may depend on earlier inputs, which are bound
+/// to fresh local variables, starting at the given local size.
+pub fn function_type_inputs(
+    globals: &Globals,
+    mut local_size: LocalSize,
+    r#type: &Arc<Value>,
+) -> Vec<(Option<String>, Arc<Value>)> {
+    let mut inputs = Vec::new();
+    let mut r#type = r#type.clone();
+
+    loop {
+        let output_type = match r#type.force(globals) {
+            Value::FunctionType(input_name_hint, input_type, output_closure) => {
+                inputs.push((input_name_hint.clone(), input_type.clone()));
+                let local = Arc::new(Value::local(local_size.next_level(), []));
+                output_closure.apply(globals, local)
+            }
+            _ => return inputs,
+        };
+        r#type = output_type;
+        local_size = local_size.increment();
+    }
+}
+
 /// Return the type of the record elimination.
 pub fn record_elim_type(
     globals: &Globals,
@@ -627,6 +704,24 @@ fn is_equal_function_closure(
     is_equal(globals, local_size.increment(), &term0, &term1)
 }
 
+/// Check that a function closure is equal to a stuck value, by applying both
+/// of them to a fresh local variable.
+fn is_equal_function_eta(
+    globals: &Globals,
+    local_size: LocalSize,
+    closure: &FunctionClosure,
+    value: &Value,
+) -> bool {
+    let local = Arc::new(Value::local(local_size.next_level(), []));
+    let term0 = closure.apply(globals, local.clone());
+    let term1 = function_elim(
+        globals,
+        Arc::new(value.clone()),
+        Arc::new(LazyValue::new(local)),
+    );
+    is_equal(globals, local_size.increment(), &term0, &term1)
+}
+
 /// Check that one record closure is equal to another record closure.
 fn is_equal_record_closure(
     globals: &Globals,
@@ -695,6 +790,13 @@ pub fn is_equal(globals: &Globals, local_size: LocalSize, value0: &Value, value1
         (Value::FunctionTerm(_, output_closure0), Value::FunctionTerm(_, output_closure1)) => {
             is_equal_function_closure(globals, local_size, output_closure0, output_closure1)
         }
+        // Eta-conversion, eg. `f` is equal to `fun x => f x`
+        (Value::FunctionTerm(_, output_closure), Value::Stuck(_, _)) => {
+            is_equal_function_eta(globals, local_size, output_closure, value1)
+        }
+        (Value::Stuck(_, _), Value::FunctionTerm(_, output_closure)) => {
+            is_equal_function_eta(globals, local_size, output_closure, value0)
+        }
 
         (Value::RecordType(labels0, closure0), Value::RecordType(labels1, closure1))
         | (Value::RecordTerm(labels0, closure0), Value::RecordTerm(labels1, closure1)) => {
@@ -722,3 +824,335 @@ pub fn is_equal(globals: &Globals, local_size: LocalSize, value0: &Value, value1
         (_, _) => false,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::core::LocalIndex;
+
+    fn term(data: TermData) -> Arc<Term> {
+        Arc::new(Term::generated(data))
+    }
+
+    /// Values that should survive being read back and evaluated again.
+    fn representative_values(globals: &Globals) -> Vec<Arc<Value>> {
+        let mut values = vec![
+            Arc::new(Value::TypeType),
+            Arc::new(Value::Constant(Constant::U8(u8::MAX))),
+            Arc::new(Value::Constant(Constant::U16(u16::MAX))),
+            Arc::new(Value::Constant(Constant::U32(u32::MAX))),
+            Arc::new(Value::Constant(Constant::U64(u64::MAX))),
+            Arc::new(Value::Constant(Constant::S8(i8::MIN))),
+            Arc::new(Value::Constant(Constant::S16(i16::MIN))),
+            Arc::new(Value::Constant(Constant::S32(i32::MIN))),
+            Arc::new(Value::Constant(Constant::S64(i64::MIN))),
+            Arc::new(Value::Constant(Constant::F32(-1.5))),
+            Arc::new(Value::Constant(Constant::F64(f64::INFINITY))),
+            Arc::new(Value::Constant(Constant::Char('\u{1F600}'))),
+            Arc::new(Value::Constant(Constant::String("hello\n".to_owned()))),
+            Arc::new(Value::global("Bool", [])),
+            Arc::new(Value::ArrayTerm(vec![Arc::new(Value::TypeType)])),
+            Arc::new(Value::ListTerm(vec![
+                Arc::new(Value::Constant(Constant::Char('a'))),
+                Arc::new(Value::Constant(Constant::Char('b'))),
+            ])),
+        ];
+
+        let terms = vec![
+            // global with a definition
+            term(TermData::Global("true".to_owned())),
+            // global applied to an input
+            term(TermData::FunctionElim(
+                term(TermData::Global("List".to_owned())),
+                term(TermData::TypeType),
+            )),
+            // fun x => x
+            term(TermData::FunctionTerm(
+                "x".to_owned(),
+                term(TermData::Local(LocalIndex(0))),
+            )),
+            // Fun (A : Type) -> A -> A
+            term(TermData::FunctionType(
+                Some("A".to_owned()),
+                term(TermData::TypeType),
+                term(TermData::FunctionType(
+                    None,
+                    term(TermData::Local(LocalIndex(0))),
+                    term(TermData::Local(LocalIndex(1))),
+                )),
+            )),
+            // Record { A : Type, a : A }
+            term(TermData::RecordType(
+                vec!["A".to_owned(), "a".to_owned()].into(),
+                vec![
+                    term(TermData::TypeType),
+                    term(TermData::Local(LocalIndex(0))),
+                ]
+                .into(),
+            )),
+            // record { A = Bool, a = true }
+            term(TermData::RecordTerm(
+                vec!["A".to_owned(), "a".to_owned()].into(),
+                vec![
+                    term(TermData::Global("Bool".to_owned())),
+                    term(TermData::Global("true".to_owned())),
+                ]
+                .into(),
+            )),
+        ];
+
+        for term in &terms {
+            values.push(eval(globals, &mut Locals::new(), term));
+        }
+
+        values
+    }
+
+    #[test]
+    fn read_back_eval_round_trip() {
+        let globals = Globals::default();
+        let local_size = Locals::<Arc<Value>>::new().size();
+
+        for unfold in &[Unfold::Never, Unfold::Always] {
+            for value in representative_values(&globals) {
+                let term = read_back(&globals, local_size, *unfold, &value);
+                let value_again = eval(&globals, &mut Locals::new(), &term);
+
+                assert!(
+                    is_equal(&globals, local_size, &value, &value_again),
+                    "round-trip failed for {:?} ({:?})",
+                    value,
+                    unfold,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn normalize_stuck_input() {
+        let globals = Globals::default();
+
+        // List ((fun x => x) Type)
+        let term = Term::generated(TermData::FunctionElim(
+            term(TermData::Global("List".to_owned())),
+            term(TermData::FunctionElim(
+                term(TermData::FunctionTerm(
+                    "x".to_owned(),
+                    term(TermData::Local(LocalIndex(0))),
+                )),
+                term(TermData::TypeType),
+            )),
+        ));
+
+        let term = normalize(&globals, &mut Locals::new(), &term);
+
+        match &term.data {
+            TermData::FunctionElim(head, input) => {
+                assert!(matches!(&head.data, TermData::Global(name) if name == "List"));
+                assert!(matches!(input.data, TermData::TypeType));
+            }
+            _ => panic!("expected a function elimination, found {:?}", term),
+        }
+    }
+
+    #[test]
+    fn normalize_elim_of_bound_record() {
+        let globals = Globals::default();
+        // record { a = Type }
+        let record_term = term(TermData::RecordTerm(
+            vec!["a".to_owned()].into(),
+            vec![term(TermData::TypeType)].into(),
+        ));
+        // r.a
+        let elim_term = term(TermData::RecordElim(
+            term(TermData::Local(LocalIndex(0))),
+            "a".to_owned(),
+        ));
+
+        // (fun r => r.a) (record { a = Type })
+        let term = Term::generated(TermData::FunctionElim(
+            term(TermData::FunctionTerm("r".to_owned(), elim_term.clone())),
+            record_term.clone(),
+        ));
+        let term = normalize(&globals, &mut Locals::new(), &term);
+        assert!(matches!(term.data, TermData::TypeType), "{:?}", term);
+
+        // r.a, with `r` defined as `record { a = Type }`
+        let mut locals = Locals::new();
+        locals.push(eval(&globals, &mut Locals::new(), &record_term));
+        let term = normalize(&globals, &mut locals, &elim_term);
+        assert!(matches!(term.data, TermData::TypeType), "{:?}", term);
+    }
+
+    #[test]
+    fn opaque_globals_are_not_unfolded() {
+        use fxhash::FxHashMap;
+
+        let mut entries = FxHashMap::default();
+        entries.insert("Bool".to_owned(), (term(TermData::TypeType), None));
+        entries.insert(
+            "Truth".to_owned(),
+            (
+                term(TermData::TypeType),
+                Some(term(TermData::Global("Bool".to_owned()))),
+            ),
+        );
+        let mut globals = Globals::new(entries);
+        let truth = Term::generated(TermData::Global("Truth".to_owned()));
+
+        let term = normalize(&globals, &mut Locals::new(), &truth);
+        assert!(matches!(&term.data, TermData::Global(name) if name == "Bool"));
+
+        globals.set_opaque("Truth", true);
+        let term = normalize(&globals, &mut Locals::new(), &truth);
+        assert!(matches!(&term.data, TermData::Global(name) if name == "Truth"));
+
+        globals.set_opaque("Truth", false);
+        let term = normalize(&globals, &mut Locals::new(), &truth);
+        assert!(matches!(&term.data, TermData::Global(name) if name == "Bool"));
+    }
+
+    #[test]
+    fn global_definitions_are_evaluated_once() {
+        let globals = Globals::default();
+        let type_term = Term::generated(TermData::Global("Type".to_owned()));
+
+        let value0 = eval(&globals, &mut Locals::new(), &type_term);
+        let value1 = eval(&globals, &mut Locals::new(), &type_term);
+
+        match (value0.as_ref(), value1.as_ref()) {
+            (Value::Unstuck(_, _, lazy_value0), Value::Unstuck(_, _, lazy_value1)) => {
+                assert!(Arc::ptr_eq(lazy_value0, lazy_value1));
+                let forced_value0 = LazyValue::force(lazy_value0, &globals);
+                let forced_value1 = LazyValue::force(lazy_value1, &globals);
+                assert!(Arc::ptr_eq(forced_value0, forced_value1));
+            }
+            _ => panic!(
+                "expected unstuck values, found {:?} and {:?}",
+                value0, value1
+            ),
+        }
+    }
+
+    #[test]
+    fn normalize_unknown_global() {
+        let globals = Globals::default();
+
+        // unknown Type
+        let term = Term::generated(TermData::FunctionElim(
+            term(TermData::Global("unknown".to_owned())),
+            term(TermData::TypeType),
+        ));
+        let term = normalize(&globals, &mut Locals::new(), &term);
+
+        match &term.data {
+            TermData::FunctionElim(head, input) => {
+                assert!(matches!(&head.data, TermData::Global(name) if name == "unknown"));
+                assert!(matches!(input.data, TermData::TypeType));
+            }
+            _ => panic!("expected a function elimination, found {:?}", term),
+        }
+    }
+
+    #[test]
+    fn is_equal_eta() {
+        let globals = Globals::default();
+        let mut locals = Locals::new();
+        let f = Arc::new(Value::local(locals.size().next_level(), []));
+        locals.push(f.clone());
+
+        // fun x => f x
+        let eta_expanded_f = eval(
+            &globals,
+            &mut locals,
+            &Term::generated(TermData::FunctionTerm(
+                "x".to_owned(),
+                term(TermData::FunctionElim(
+                    term(TermData::Local(LocalIndex(1))),
+                    term(TermData::Local(LocalIndex(0))),
+                )),
+            )),
+        );
+        // fun x => f Type
+        let const_f = eval(
+            &globals,
+            &mut locals,
+            &Term::generated(TermData::FunctionTerm(
+                "x".to_owned(),
+                term(TermData::FunctionElim(
+                    term(TermData::Local(LocalIndex(1))),
+                    term(TermData::TypeType),
+                )),
+            )),
+        );
+
+        assert!(is_equal(&globals, locals.size(), &f, &eta_expanded_f));
+        assert!(is_equal(&globals, locals.size(), &eta_expanded_f, &f));
+        assert!(!is_equal(&globals, locals.size(), &f, &const_f));
+        assert!(!is_equal(&globals, locals.size(), &const_f, &f));
+    }
+
+    #[test]
+    fn normalize_long_spine() {
+        let globals = Globals::default();
+        let mut locals = Locals::new();
+        locals.push(Arc::new(Value::local(locals.size().next_level(), [])));
+
+        // f Type Type ... Type
+        let input_count = 1000;
+        let term = (0..input_count).fold(
+            Term::generated(TermData::Local(LocalIndex(0))),
+            |head, _| {
+                Term::generated(TermData::FunctionElim(
+                    Arc::new(head),
+                    term(TermData::TypeType),
+                ))
+            },
+        );
+
+        let mut term = &normalize(&globals, &mut locals, &term);
+        let mut seen_input_count = 0;
+        while let TermData::FunctionElim(head, input) = &term.data {
+            assert!(matches!(input.data, TermData::TypeType));
+            term = head.as_ref();
+            seen_input_count += 1;
+        }
+        assert!(matches!(term.data, TermData::Local(LocalIndex(0))));
+        assert_eq!(seen_input_count, input_count);
+    }
+
+    #[test]
+    fn function_type_inputs_of_dependent_type() {
+        let globals = Globals::default();
+        let local_size = Locals::<Arc<Value>>::new().size();
+
+        // Fun (A : Type) -> A -> Type
+        let r#type = eval(
+            &globals,
+            &mut Locals::new(),
+            &Term::generated(TermData::FunctionType(
+                Some("A".to_owned()),
+                term(TermData::TypeType),
+                term(TermData::FunctionType(
+                    None,
+                    term(TermData::Local(LocalIndex(0))),
+                    term(TermData::TypeType),
+                )),
+            )),
+        );
+
+        match function_type_inputs(&globals, local_size, &r#type).as_slice() {
+            [(Some(name0), type0), (None, type1)] => {
+                assert_eq!(name0, "A");
+                assert!(matches!(type0.as_ref(), Value::TypeType));
+                // The second input type refers to the first input
+                let type1 = read_back(&globals, local_size.increment(), Unfold::Never, type1);
+                assert!(matches!(type1.data, TermData::Local(LocalIndex(0))));
+            }
+            inputs => panic!("unexpected inputs: {:?}", inputs),
+        }
+
+        let r#type = Arc::new(Value::TypeType);
+        assert!(function_type_inputs(&globals, local_size, &r#type).is_empty());
+    }
+}
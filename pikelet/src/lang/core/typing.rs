@@ -11,12 +11,17 @@
 
 use contracts::debug_ensures;
 use crossbeam_channel::Sender;
+use std::fmt;
 use std::sync::Arc;
 
 use crate::lang::core::semantics::{self, Elim, Unfold, Value};
 use crate::lang::core::{Constant, Globals, LocalSize, Locals, Term, TermData};
 use crate::reporting::{AmbiguousTerm, CoreTypingMessage, ExpectedType, Message};
 
+/// The default maximum nesting depth of terms that the type checker and the
+/// elaborator will descend into before giving up.
+pub const DEFAULT_RECURSION_LIMIT: usize = 512;
+
 /// The state of the type checker.
 pub struct State<'me> {
     /// Global definition environment.
@@ -25,10 +30,82 @@ pub struct State<'me> {
     local_declarations: Locals<Arc<Value>>,
     /// Local value environment (used for evaluation).
     local_definitions: Locals<Arc<Value>>,
+    /// Whether to trace the premises of the typing rules being applied.
+    trace_premises: bool,
+    /// The typing rules currently being applied, from outermost to innermost,
+    /// along with the number of their premises that have been started. These
+    /// are only recorded if `trace_premises` is enabled, and are used to give
+    /// context to internal errors.
+    premise_trace: Vec<(&'static str, &'static str, usize)>,
+    /// Whether to record the derivations of the typing judgements.
+    record_derivations: bool,
+    /// The derivations of the typing rules currently being applied, from
+    /// outermost to innermost.
+    pending_derivations: Vec<Derivation>,
+    /// The derivations of the typing judgements that have been completed.
+    derivations: Vec<Derivation>,
+    /// The current nesting depth of the type checker.
+    depth: usize,
+    /// The maximum nesting depth of the type checker.
+    recursion_limit: usize,
     /// The diagnostic messages accumulated during type checking.
     message_tx: Sender<Message>,
 }
 
+/// A derivation of a typing judgement, recording the typing rule that was
+/// applied to a term, along with the derivations of its premises.
+#[derive(Clone, Debug)]
+pub struct Derivation {
+    /// The typing rule that was applied, eg. `synth function elimination`.
+    pub rule: String,
+    /// The term that the rule was applied to.
+    pub term: Term,
+    /// The type of the term, as synthesized or checked against.
+    pub r#type: Term,
+    /// The derivations of the premises of the rule.
+    pub premises: Vec<Derivation>,
+}
+
+impl Derivation {
+    fn fmt_indented(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        writeln!(
+            f,
+            "{:indent$}{}: {} : {}",
+            "",
+            self.rule,
+            self.term,
+            self.r#type,
+            indent = depth * 4,
+        )?;
+        for premise in &self.premises {
+            premise.fmt_indented(f, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Derivation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_indented(f, 0)
+    }
+}
+
+/// A premise of a typing rule, eg. the second premise of
+/// `synth function elimination`, which checks the input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Premise {
+    /// The typing rule that the premise belongs to.
+    pub rule: String,
+    /// The position of the premise in the rule, starting from `1`.
+    pub index: usize,
+}
+
+impl fmt::Display for Premise {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}, premise {}", self.rule, self.index)
+    }
+}
+
 impl<'me> State<'me> {
     /// Construct a new type checker state.
     pub fn new(globals: &'me Globals, message_tx: Sender<Message>) -> State<'me> {
@@ -36,10 +113,43 @@ impl<'me> State<'me> {
             globals,
             local_declarations: Locals::new(),
             local_definitions: Locals::new(),
+            trace_premises: false,
+            premise_trace: Vec::new(),
+            record_derivations: false,
+            pending_derivations: Vec::new(),
+            derivations: Vec::new(),
+            depth: 0,
+            recursion_limit: DEFAULT_RECURSION_LIMIT,
             message_tx,
         }
     }
 
+    /// Set whether the premises of the typing rules being applied should be
+    /// traced, allowing internal errors to report the premise they were found
+    /// in. This is disabled by default.
+    pub fn set_trace_premises(&mut self, trace_premises: bool) {
+        self.trace_premises = trace_premises;
+    }
+
+    /// Set whether the derivations of typing judgements should be recorded.
+    /// This is disabled by default.
+    pub fn set_record_derivations(&mut self, record_derivations: bool) {
+        self.record_derivations = record_derivations;
+    }
+
+    /// Take the derivations of the typing judgements that have been recorded
+    /// so far.
+    pub fn take_derivations(&mut self) -> Vec<Derivation> {
+        std::mem::take(&mut self.derivations)
+    }
+
+    /// Set the maximum nesting depth of terms that the type checker will
+    /// descend into before reporting an error. Defaults to
+    /// [`DEFAULT_RECURSION_LIMIT`].
+    pub fn set_recursion_limit(&mut self, recursion_limit: usize) {
+        self.recursion_limit = recursion_limit;
+    }
+
     /// Get the size of the local environment.
     fn size(&self) -> LocalSize {
         self.local_definitions.size()
@@ -75,6 +185,92 @@ impl<'me> State<'me> {
         self.message_tx.send(message.into()).unwrap();
     }
 
+    /// Apply a typing rule to a term, tracing it for use in internal errors
+    /// and recording it in derivations if either of these are enabled. An
+    /// error is reported instead if this would exceed the recursion limit.
+    fn traced(
+        &mut self,
+        mode: &'static str,
+        term: &Term,
+        f: impl FnOnce(&mut Self) -> Arc<Value>,
+    ) -> Arc<Value> {
+        if self.depth >= self.recursion_limit {
+            self.report(CoreTypingMessage::RecursionLimitExceeded {
+                location: term.location,
+                limit: self.recursion_limit,
+            });
+            return Arc::new(Value::Error);
+        }
+
+        let description = term_description(term);
+        if self.trace_premises {
+            if let Some((_, _, premise_count)) = self.premise_trace.last_mut() {
+                *premise_count += 1;
+            }
+            self.premise_trace.push((mode, description, 0));
+        }
+        if self.record_derivations {
+            self.pending_derivations.push(Derivation {
+                rule: format!("{} {}", mode, description),
+                term: term.clone(),
+                r#type: Term::generated(TermData::Error),
+                premises: Vec::new(),
+            });
+        }
+
+        self.depth += 1;
+        let r#type = f(self);
+        self.depth -= 1;
+
+        if self.trace_premises {
+            self.premise_trace.pop();
+        }
+        if let Some(mut derivation) = self.pending_derivations.pop() {
+            derivation.r#type = self.read_back(&r#type);
+            match self.pending_derivations.last_mut() {
+                Some(parent) => parent.premises.push(derivation),
+                None => self.derivations.push(derivation),
+            }
+        }
+
+        r#type
+    }
+
+    /// The premise of the enclosing typing rule that is currently being
+    /// applied, if premises are being traced.
+    fn current_premise(&self) -> Option<Premise> {
+        let index = self.premise_trace.len().checked_sub(2)?;
+        self.premise_at(index)
+    }
+
+    /// The most recently started premise of the current typing rule, if
+    /// premises are being traced.
+    fn last_premise(&self) -> Option<Premise> {
+        let index = self.premise_trace.len().checked_sub(1)?;
+        self.premise_at(index)
+    }
+
+    /// A premise of the typing rule that is currently being applied, if
+    /// premises are being traced. This is used for premises that are checked
+    /// directly by the rule, like looking up the type of a local variable.
+    fn premise_of_current_rule(&self, index: usize) -> Option<Premise> {
+        let (mode, description, _) = self.premise_trace.last()?;
+        Some(Premise {
+            rule: format!("{} {}", mode, description),
+            index,
+        })
+    }
+
+    fn premise_at(&self, index: usize) -> Option<Premise> {
+        match self.premise_trace[index] {
+            (_, _, 0) => None,
+            (mode, description, premise_count) => Some(Premise {
+                rule: format!("{} {}", mode, description),
+                index: premise_count,
+            }),
+        }
+    }
+
     /// Evaluate a [`Term`] into a [`Value`].
     ///
     /// [`Value`]: crate::lang::core::semantics::Value
@@ -130,6 +326,7 @@ impl<'me> State<'me> {
                 self.report(CoreTypingMessage::MismatchedTypes {
                     found_type: self.read_back(&r#type),
                     expected_type: ExpectedType::Universe,
+                    premise: self.last_premise(),
                 });
                 false
             }
@@ -140,6 +337,13 @@ impl<'me> State<'me> {
     #[debug_ensures(self.local_declarations.size() == old(self.local_declarations.size()))]
     #[debug_ensures(self.local_definitions.size() == old(self.local_definitions.size()))]
     pub fn check_type(&mut self, term: &Term, expected_type: &Arc<Value>) {
+        self.traced("check", term, |state| {
+            state.check_type_untraced(term, expected_type);
+            expected_type.clone()
+        });
+    }
+
+    fn check_type_untraced(&mut self, term: &Term, expected_type: &Arc<Value>) {
         match (&term.data, expected_type.force(self.globals)) {
             (_, Value::Error) => {}
 
@@ -220,6 +424,7 @@ impl<'me> State<'me> {
                                         Elim::Function(entry_type.clone()),
                                     ],
                                 )),
+                                premise: self.current_premise(),
                             });
                         }
                     }
@@ -247,6 +452,7 @@ impl<'me> State<'me> {
                 found_type => self.report(CoreTypingMessage::MismatchedTypes {
                     found_type: self.read_back(&found_type),
                     expected_type: ExpectedType::Type(self.read_back(expected_type)),
+                    premise: self.current_premise(),
                 }),
             },
         }
@@ -256,6 +462,10 @@ impl<'me> State<'me> {
     #[debug_ensures(self.local_declarations.size() == old(self.local_declarations.size()))]
     #[debug_ensures(self.local_definitions.size() == old(self.local_definitions.size()))]
     pub fn synth_type(&mut self, term: &Term) -> Arc<Value> {
+        self.traced("synth", term, |state| state.synth_type_untraced(term))
+    }
+
+    fn synth_type_untraced(&mut self, term: &Term) -> Arc<Value> {
         match &term.data {
             TermData::Global(name) => match self.globals.get(name) {
                 Some((r#type, _)) => self.eval(r#type),
@@ -269,7 +479,11 @@ impl<'me> State<'me> {
             TermData::Local(local_index) => match self.local_declarations.get(*local_index) {
                 Some(r#type) => r#type.clone(),
                 None => {
-                    self.report(CoreTypingMessage::UnboundLocal);
+                    self.report(CoreTypingMessage::UnboundLocal {
+                        location: term.location,
+                        local_index: *local_index,
+                        premise: self.premise_of_current_rule(1),
+                    });
                     Arc::new(Value::Error)
                 }
             },
@@ -338,20 +552,22 @@ impl<'me> State<'me> {
 
                 let mut duplicate_labels = Vec::new();
                 let mut seen_labels = BTreeSet::new();
+                let mut entry_count = 0;
 
                 for (name, r#type) in Iterator::zip(labels.iter(), types.iter()) {
                     if !seen_labels.insert(name) {
                         duplicate_labels.push(name.clone());
                     }
                     if !self.is_type(r#type) {
-                        self.pop_many_locals(seen_labels.len());
+                        self.pop_many_locals(entry_count);
                         return Arc::new(Value::Error);
                     }
                     let r#type = self.eval(r#type);
                     self.push_local_param(r#type);
+                    entry_count += 1;
                 }
 
-                self.pop_many_locals(seen_labels.len());
+                self.pop_many_locals(entry_count);
 
                 if !duplicate_labels.is_empty() {
                     self.report(CoreTypingMessage::InvalidRecordType { duplicate_labels });
@@ -404,4 +620,480 @@ impl<'me> State<'me> {
             TermData::Error => Arc::new(Value::Error),
         }
     }
+
+    /// Fully normalize a well-typed term, keeping its annotations in place,
+    /// and annotating the results of function eliminations with their types.
+    ///
+    /// Unlike [`semantics::normalize`], which discards annotations during
+    /// evaluation, this is intended for consumers of the elaborated term that
+    /// need to know the types of its subterms. Redexes are still reduced, so
+    /// any annotations inside them are lost.
+    #[debug_ensures(self.local_declarations.size() == old(self.local_declarations.size()))]
+    #[debug_ensures(self.local_definitions.size() == old(self.local_definitions.size()))]
+    pub fn normalize_preserving_annotations(&mut self, term: &Term) -> Term {
+        self.annotate_synth(term).0
+    }
+
+    /// Normalize a term that is an element of the expected type, preserving
+    /// its annotations.
+    fn annotate_check(&mut self, term: &Term, expected_type: &Arc<Value>) -> Term {
+        match (&term.data, expected_type.force(self.globals)) {
+            (
+                TermData::FunctionTerm(input_name, output_term),
+                Value::FunctionType(_, input_type, output_closure),
+            ) => {
+                let input_term = self.push_local_param(input_type.clone());
+                let output_type = output_closure.apply(self.globals, input_term);
+                let output_term = self.annotate_check(output_term, &output_type);
+                self.pop_local();
+
+                let term_data = TermData::FunctionTerm(input_name.clone(), Arc::new(output_term));
+                Term::new(term.location, term_data)
+            }
+
+            (TermData::RecordTerm(labels, terms), Value::RecordType(_, closure)) => {
+                let mut pending_terms = terms.iter();
+                let mut entry_terms = Vec::with_capacity(terms.len());
+
+                closure.for_each_entry(self.globals, |r#type| match pending_terms.next() {
+                    Some(term) => {
+                        entry_terms.push(Arc::new(self.annotate_check(term, &r#type)));
+                        let value = self.eval(term);
+                        self.push_local(value.clone(), r#type);
+                        value
+                    }
+                    None => Arc::new(Value::Error),
+                });
+
+                self.pop_many_locals(entry_terms.len());
+
+                let term_data = TermData::RecordTerm(labels.clone(), entry_terms.into());
+                Term::new(term.location, term_data)
+            }
+
+            (TermData::ArrayTerm(entry_terms), forced_type) => match forced_type.try_global() {
+                Some(("Array", [_, Elim::Function(entry_type)])) => {
+                    let entry_type = entry_type.force(self.globals).clone();
+                    let entry_terms = (entry_terms.iter())
+                        .map(|entry_term| Arc::new(self.annotate_check(entry_term, &entry_type)))
+                        .collect();
+
+                    Term::new(term.location, TermData::ArrayTerm(entry_terms))
+                }
+                Some(_) | None => self.normalize(term),
+            },
+            (TermData::ListTerm(entry_terms), forced_type) => match forced_type.try_global() {
+                Some(("List", [Elim::Function(entry_type)])) => {
+                    let entry_type = entry_type.force(self.globals).clone();
+                    let entry_terms = (entry_terms.iter())
+                        .map(|entry_term| Arc::new(self.annotate_check(entry_term, &entry_type)))
+                        .collect();
+
+                    Term::new(term.location, TermData::ListTerm(entry_terms))
+                }
+                Some(_) | None => self.normalize(term),
+            },
+
+            (_, _) => self.annotate_synth(term).0,
+        }
+    }
+
+    /// Normalize a term, preserving its annotations, and returning its type.
+    fn annotate_synth(&mut self, term: &Term) -> (Term, Arc<Value>) {
+        match &term.data {
+            TermData::Ann(annotated_term, r#type) => {
+                let r#type = self.eval(r#type);
+                let annotated_term = self.annotate_check(annotated_term, &r#type);
+                let type_term = self.normalize_value(&r#type);
+
+                let term_data = TermData::Ann(Arc::new(annotated_term), Arc::new(type_term));
+                (Term::new(term.location, term_data), r#type)
+            }
+
+            TermData::FunctionElim(head_term, input_term) => {
+                let (core_head_term, head_type) = self.annotate_synth(head_term);
+                let (input_type, output_type) = match head_type.force(self.globals) {
+                    Value::FunctionType(_, input_type, output_closure) => {
+                        let input_value = self.eval(input_term);
+                        let output_type = output_closure.apply(self.globals, input_value);
+                        (input_type.clone(), output_type)
+                    }
+                    _ => return (self.normalize(term), Arc::new(Value::Error)),
+                };
+
+                let core_term = match &strip_annotations(&core_head_term).data {
+                    TermData::FunctionTerm(_, _) => self.normalize(term),
+                    _ => {
+                        let core_input_term = self.annotate_check(input_term, &input_type);
+                        let term_data = TermData::FunctionElim(
+                            Arc::new(core_head_term),
+                            Arc::new(core_input_term),
+                        );
+                        Term::new(term.location, term_data)
+                    }
+                };
+                let type_term = self.normalize_value(&output_type);
+
+                let term_data = TermData::Ann(Arc::new(core_term), Arc::new(type_term));
+                (Term::new(term.location, term_data), output_type)
+            }
+
+            TermData::RecordElim(head_term, label) => {
+                let (core_head_term, head_type) = self.annotate_synth(head_term);
+                let r#type = (self.record_elim_type(head_term, &head_type, label))
+                    .unwrap_or_else(|| Arc::new(Value::Error));
+
+                let core_term = match &strip_annotations(&core_head_term).data {
+                    TermData::RecordTerm(_, _) => self.normalize(term),
+                    _ => {
+                        let term_data =
+                            TermData::RecordElim(Arc::new(core_head_term), label.clone());
+                        Term::new(term.location, term_data)
+                    }
+                };
+
+                (core_term, r#type)
+            }
+
+            _ => {
+                let r#type = self.synth_type(term);
+                (self.normalize(term), r#type)
+            }
+        }
+    }
+
+    /// Fully normalize a term.
+    fn normalize(&mut self, term: &Term) -> Term {
+        semantics::normalize(self.globals, &mut self.local_definitions, term)
+    }
+
+    /// Read back a value into a fully normalized term.
+    fn normalize_value(&self, value: &Value) -> Term {
+        semantics::read_back(self.globals, self.size(), Unfold::Always, value)
+    }
+}
+
+/// Remove the annotations from the outside of a term.
+fn strip_annotations(mut term: &Term) -> &Term {
+    while let TermData::Ann(annotated_term, _) = &term.data {
+        term = annotated_term;
+    }
+    term
+}
+
+/// A short description of a term, for use in internal errors.
+fn term_description(term: &Term) -> &'static str {
+    match &term.data {
+        TermData::Global(_) => "global",
+        TermData::Local(_) => "local",
+        TermData::Ann(_, _) => "annotated term",
+        TermData::TypeType => "type of types",
+        TermData::FunctionType(_, _, _) => "function type",
+        TermData::FunctionTerm(_, _) => "function term",
+        TermData::FunctionElim(_, _) => "function elimination",
+        TermData::RecordType(_, _) => "record type",
+        TermData::RecordTerm(_, _) => "record term",
+        TermData::RecordElim(_, _) => "record elimination",
+        TermData::ArrayTerm(_) => "array term",
+        TermData::ListTerm(_) => "list term",
+        TermData::Constant(_) => "constant",
+        TermData::Error => "error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::core::LocalIndex;
+
+    #[test]
+    fn unbound_local_reports_premise() {
+        let globals = Globals::default();
+        let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+
+        // #0 : Type, where #0 is not bound
+        let term = Term::generated(TermData::Ann(
+            Arc::new(Term::generated(TermData::Local(LocalIndex(0)))),
+            Arc::new(Term::generated(TermData::TypeType)),
+        ));
+        let mut state = State::new(&globals, messages_tx);
+        state.set_trace_premises(true);
+        state.synth_type(&term);
+
+        let messages = messages_rx.try_iter().collect::<Vec<_>>();
+        match messages.as_slice() {
+            [Message::CoreTyping(CoreTypingMessage::UnboundLocal {
+                local_index,
+                premise,
+                ..
+            })] => {
+                assert_eq!(*local_index, LocalIndex(0));
+                let premise = premise.as_ref().unwrap();
+                assert_eq!(premise.to_string(), "synth local, premise 1");
+            }
+            messages => panic!("unexpected messages: {:?}", messages),
+        }
+    }
+
+    #[test]
+    fn unbound_global() {
+        let globals = Globals::default();
+        let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+
+        let term = Term::generated(TermData::Global("unknown".to_owned()));
+        State::new(&globals, messages_tx).synth_type(&term);
+
+        let messages = messages_rx.try_iter().collect::<Vec<_>>();
+        match messages.as_slice() {
+            [Message::CoreTyping(CoreTypingMessage::UnboundGlobal { name })] => {
+                assert_eq!(name, "unknown");
+            }
+            messages => panic!("unexpected messages: {:?}", messages),
+        }
+    }
+
+    #[test]
+    fn mismatched_function_input_reports_premise() {
+        let globals = Globals::default();
+        let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+
+        // List 1
+        let term = Term::generated(TermData::FunctionElim(
+            Arc::new(Term::generated(TermData::Global("List".to_owned()))),
+            Arc::new(Term::generated(TermData::Constant(Constant::U32(1)))),
+        ));
+        let mut state = State::new(&globals, messages_tx);
+        state.set_trace_premises(true);
+        state.synth_type(&term);
+
+        let messages = messages_rx.try_iter().collect::<Vec<_>>();
+        match messages.as_slice() {
+            [Message::CoreTyping(CoreTypingMessage::MismatchedTypes { premise, .. })] => {
+                let premise = premise.as_ref().unwrap();
+                assert_eq!(premise.rule, "synth function elimination");
+                assert_eq!(premise.index, 2);
+            }
+            messages => panic!("unexpected messages: {:?}", messages),
+        }
+    }
+
+    #[test]
+    fn premises_are_not_traced_by_default() {
+        let globals = Globals::default();
+        let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+
+        // List 1
+        let term = Term::generated(TermData::FunctionElim(
+            Arc::new(Term::generated(TermData::Global("List".to_owned()))),
+            Arc::new(Term::generated(TermData::Constant(Constant::U32(1)))),
+        ));
+        State::new(&globals, messages_tx).synth_type(&term);
+
+        let messages = messages_rx.try_iter().collect::<Vec<_>>();
+        match messages.as_slice() {
+            [Message::CoreTyping(CoreTypingMessage::MismatchedTypes { premise, .. })] => {
+                assert_eq!(*premise, None);
+            }
+            messages => panic!("unexpected messages: {:?}", messages),
+        }
+    }
+
+    #[test]
+    fn record_derivations() {
+        let globals = Globals::default();
+        let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+
+        // (fun a => a : Type -> Type) Type
+        let term = Term::generated(TermData::FunctionElim(
+            Arc::new(Term::generated(TermData::Ann(
+                Arc::new(Term::generated(TermData::FunctionTerm(
+                    "a".to_owned(),
+                    Arc::new(Term::generated(TermData::Local(LocalIndex(0)))),
+                ))),
+                Arc::new(Term::generated(TermData::FunctionType(
+                    None,
+                    Arc::new(Term::generated(TermData::TypeType)),
+                    Arc::new(Term::generated(TermData::TypeType)),
+                ))),
+            ))),
+            Arc::new(Term::generated(TermData::TypeType)),
+        ));
+
+        let mut state = State::new(&globals, messages_tx);
+        state.set_record_derivations(true);
+        state.synth_type(&term);
+        assert_eq!(messages_rx.try_iter().count(), 0);
+
+        match state.take_derivations().as_slice() {
+            [derivation] => {
+                assert_eq!(derivation.rule, "synth function elimination");
+                assert!(matches!(derivation.r#type.data, TermData::TypeType));
+
+                let premise_rules = derivation.premises.iter().map(|p| p.rule.as_str());
+                assert_eq!(
+                    premise_rules.collect::<Vec<_>>(),
+                    ["synth annotated term", "check type of types"],
+                );
+
+                let annotation_rules = derivation.premises[0].premises.iter();
+                assert_eq!(
+                    annotation_rules
+                        .map(|p| p.rule.as_str())
+                        .collect::<Vec<_>>(),
+                    ["synth function type", "check function term"],
+                );
+            }
+            derivations => panic!("unexpected derivations: {:?}", derivations),
+        }
+        assert!(state.take_derivations().is_empty());
+    }
+
+    fn term(data: TermData) -> Arc<Term> {
+        Arc::new(Term::generated(data))
+    }
+
+    #[test]
+    fn normalize_preserving_annotations_keeps_annotations() {
+        let globals = Globals::default();
+        let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+
+        // Type : Type
+        let term = Term::generated(TermData::Ann(
+            term(TermData::TypeType),
+            term(TermData::Global("Type".to_owned())),
+        ));
+
+        let normal_term = semantics::normalize(&globals, &mut Locals::new(), &term);
+        assert!(matches!(normal_term.data, TermData::TypeType));
+
+        let mut state = State::new(&globals, messages_tx);
+        let normal_term = state.normalize_preserving_annotations(&term);
+        assert_eq!(messages_rx.try_iter().count(), 0);
+        match &normal_term.data {
+            TermData::Ann(term, r#type) => {
+                assert!(matches!(term.data, TermData::TypeType));
+                assert!(matches!(r#type.data, TermData::TypeType));
+            }
+            _ => panic!("expected an annotated term, found {:?}", normal_term),
+        }
+    }
+
+    #[test]
+    fn normalize_preserving_annotations_keeps_nested_annotations() {
+        let globals = Globals::default();
+        let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+
+        // (List : Type -> Type) (Bool : Type) : Type
+        let term = Term::generated(TermData::Ann(
+            term(TermData::FunctionElim(
+                term(TermData::Ann(
+                    term(TermData::Global("List".to_owned())),
+                    term(TermData::FunctionType(
+                        None,
+                        term(TermData::TypeType),
+                        term(TermData::TypeType),
+                    )),
+                )),
+                term(TermData::Ann(
+                    term(TermData::Global("Bool".to_owned())),
+                    term(TermData::TypeType),
+                )),
+            )),
+            term(TermData::TypeType),
+        ));
+
+        let mut state = State::new(&globals, messages_tx);
+        let normal_term = state.normalize_preserving_annotations(&term);
+        assert_eq!(messages_rx.try_iter().count(), 0);
+
+        // The function elimination is annotated with the type of its result
+        let elim_term = match &normal_term.data {
+            TermData::Ann(term, _) => match &term.data {
+                TermData::Ann(term, r#type) => {
+                    assert!(matches!(r#type.data, TermData::TypeType));
+                    term
+                }
+                _ => panic!("expected an annotated term, found {:?}", term),
+            },
+            _ => panic!("expected an annotated term, found {:?}", normal_term),
+        };
+        match &elim_term.data {
+            TermData::FunctionElim(head_term, input_term) => {
+                assert!(matches!(
+                    &head_term.data,
+                    TermData::Ann(term, r#type)
+                        if matches!(&term.data, TermData::Global(name) if name == "List")
+                            && matches!(r#type.data, TermData::FunctionType(_, _, _)),
+                ));
+                assert!(matches!(
+                    &input_term.data,
+                    TermData::Ann(term, r#type)
+                        if matches!(&term.data, TermData::Global(name) if name == "Bool")
+                            && matches!(r#type.data, TermData::TypeType),
+                ));
+            }
+            _ => panic!("expected a function elimination, found {:?}", elim_term),
+        }
+    }
+
+    #[test]
+    fn normalize_preserving_annotations_reduces_redexes() {
+        let globals = Globals::default();
+        let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+
+        // (fun a => a : Type -> Type) Bool
+        let term = Term::generated(TermData::FunctionElim(
+            term(TermData::Ann(
+                term(TermData::FunctionTerm(
+                    "a".to_owned(),
+                    term(TermData::Local(LocalIndex(0))),
+                )),
+                term(TermData::FunctionType(
+                    None,
+                    term(TermData::TypeType),
+                    term(TermData::TypeType),
+                )),
+            )),
+            term(TermData::Global("Bool".to_owned())),
+        ));
+
+        let mut state = State::new(&globals, messages_tx);
+        let normal_term = state.normalize_preserving_annotations(&term);
+        assert_eq!(messages_rx.try_iter().count(), 0);
+        assert!(matches!(
+            &normal_term.data,
+            TermData::Ann(term, r#type)
+                if matches!(&term.data, TermData::Global(name) if name == "Bool")
+                    && matches!(r#type.data, TermData::TypeType),
+        ));
+    }
+
+    #[test]
+    fn recursion_limit_exceeded() {
+        let globals = Globals::default();
+        let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+
+        // Type -> Type -> ... -> Type
+        let term = (0..64).fold(Term::generated(TermData::TypeType), |acc, _| {
+            let input = Arc::new(Term::generated(TermData::TypeType));
+            Term::generated(TermData::FunctionType(None, input, Arc::new(acc)))
+        });
+
+        let mut state = State::new(&globals, messages_tx);
+        state.set_recursion_limit(32);
+        state.synth_type(&term);
+        let messages = messages_rx.try_iter().collect::<Vec<_>>();
+
+        assert!(!messages.is_empty());
+        for message in &messages {
+            assert!(matches!(
+                message,
+                Message::CoreTyping(CoreTypingMessage::RecursionLimitExceeded { limit: 32, .. }),
+            ));
+        }
+
+        let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+        State::new(&globals, messages_tx).synth_type(&term);
+        assert_eq!(messages_rx.try_iter().count(), 0);
+    }
 }
@@ -3,6 +3,7 @@
 //! This is a user-friendly concrete syntax for the language.
 
 use crossbeam_channel::Sender;
+use std::fmt;
 
 use crate::lang::{FileId, Located, Location};
 use crate::reporting::Message;
@@ -20,6 +21,8 @@ pub type TypeEntry = (Located<String>, Option<Located<String>>, Term);
 pub type TermEntry = (Located<String>, Option<Located<String>>, Term);
 /// A group of function inputs that are elements of the same type.
 pub type InputGroup = (Vec<Located<String>>, Term);
+/// An input to a [function term](Term::FunctionTerm), with an optional type annotation.
+pub type FunctionInput = (Located<String>, Option<Term>);
 
 pub type Term = Located<TermData>;
 
@@ -28,8 +31,21 @@ pub type Term = Located<TermData>;
 pub enum TermData {
     /// Names.
     Name(String),
+    /// Holes, standing in for terms that have yet to be written.
+    ///
+    /// These may optionally be given a name, eg. `?goal`.
+    Hole(Option<String>),
+    /// Parenthesized terms.
+    ///
+    /// These are kept in the syntax tree so that the grouping chosen by the
+    /// user can be preserved when printing.
+    Paren(Box<Term>),
 
     /// Annotated terms.
+    ///
+    /// Annotations bind more loosely than any other term, so `f x : T`
+    /// annotates `f x`. Use parentheses to annotate a function input, eg.
+    /// `f (x : T)`.
     Ann(Box<Term>, Box<Term>),
 
     /// Function types.
@@ -43,7 +59,9 @@ pub enum TermData {
     /// Function terms.
     ///
     /// Also known as: lambda abstraction, anonymous function.
-    FunctionTerm(Vec<Located<String>>, Box<Term>),
+    ///
+    /// Inputs may be annotated with their types, eg. `fun (A : Type) a => a`.
+    FunctionTerm(Vec<FunctionInput>, Box<Term>),
     /// Function eliminations.
     ///
     /// Also known as: function application.
@@ -76,16 +94,448 @@ impl<'input> Term {
     #[allow(clippy::should_implement_trait)]
     pub fn from_str(file_id: FileId, input: &str, messages_tx: &Sender<Message>) -> Term {
         let tokens = lexer::tokens(file_id, input);
-        grammar::TermParser::new()
-            .parse(file_id, tokens)
-            .unwrap_or_else(|error| {
-                messages_tx
-                    .send(Message::from_lalrpop(file_id, error))
-                    .unwrap();
-                Term::new(
-                    Location::file_range(file_id, 0..input.len()),
-                    TermData::Error,
-                )
-            })
+        let mut errors = Vec::new();
+        let result = grammar::TermParser::new().parse(file_id, &mut errors, tokens);
+
+        // Report the errors that the parser was able to recover from
+        for error in errors {
+            messages_tx
+                .send(Message::from_lalrpop(file_id, error.error))
+                .unwrap();
+        }
+
+        result.unwrap_or_else(|error| {
+            messages_tx
+                .send(Message::from_lalrpop(file_id, error))
+                .unwrap();
+            Term::new(
+                Location::file_range(file_id, 0..input.len()),
+                TermData::Error,
+            )
+        })
+    }
+
+    /// Strip any parentheses surrounding a term.
+    pub fn strip_parens(&self) -> &Term {
+        let mut term = self;
+        while let TermData::Paren(inner_term) = &term.data {
+            term = inner_term;
+        }
+        term
+    }
+
+    /// Remove the parentheses that are not needed to preserve the structure
+    /// of the term when it is parsed again, eg. `((x) (y))` becomes `x y`,
+    /// but the parentheses in `f (g x)` are kept.
+    pub fn simplify(&self) -> Term {
+        use crate::pass::surface_to_pretty::Prec;
+
+        fn prec(term: &Term) -> Prec {
+            match &term.data {
+                TermData::Ann(_, _) => Prec::Term,
+                TermData::FunctionTerm(_, _) => Prec::Expr,
+                TermData::FunctionType(_, _) | TermData::FunctionArrowType(_, _) => Prec::Arrow,
+                TermData::FunctionElim(_, _) => Prec::App,
+                TermData::Name(_)
+                | TermData::Hole(_)
+                | TermData::Paren(_)
+                | TermData::RecordType(_)
+                | TermData::RecordTerm(_)
+                | TermData::RecordElim(_, _)
+                | TermData::SequenceTerm(_)
+                | TermData::CharTerm(_)
+                | TermData::StringTerm(_)
+                | TermData::NumberTerm(_)
+                | TermData::Error => Prec::Atomic,
+            }
+        }
+
+        fn go(term: &Term, min_prec: Prec) -> Term {
+            let go_box = |term: &Term, min_prec| Box::new(go(term, min_prec));
+            let go_entries = |entries: &[TermEntry]| {
+                (entries.iter())
+                    .map(|(label, name, term)| (label.clone(), name.clone(), go(term, Prec::Term)))
+                    .collect()
+            };
+
+            let term_data = match &term.data {
+                TermData::Paren(inner_term) => {
+                    let inner_term = go(inner_term, Prec::Term);
+                    if prec(&inner_term) >= min_prec {
+                        return inner_term;
+                    }
+                    TermData::Paren(Box::new(inner_term))
+                }
+                TermData::Ann(term, r#type) => {
+                    TermData::Ann(go_box(term, Prec::Expr), go_box(r#type, Prec::Term))
+                }
+                TermData::FunctionType(input_type_groups, output_type) => TermData::FunctionType(
+                    (input_type_groups.iter())
+                        .map(|(input_names, input_type)| {
+                            (input_names.clone(), go(input_type, Prec::Arrow))
+                        })
+                        .collect(),
+                    go_box(output_type, Prec::Arrow),
+                ),
+                TermData::FunctionArrowType(input_type, output_type) => {
+                    TermData::FunctionArrowType(
+                        go_box(input_type, Prec::App),
+                        go_box(output_type, Prec::Arrow),
+                    )
+                }
+                TermData::FunctionTerm(inputs, output_term) => TermData::FunctionTerm(
+                    (inputs.iter())
+                        .map(|(input_name, input_type)| {
+                            let input_type = input_type.as_ref();
+                            (input_name.clone(), input_type.map(|t| go(t, Prec::Arrow)))
+                        })
+                        .collect(),
+                    go_box(output_term, Prec::Expr),
+                ),
+                TermData::FunctionElim(head_term, input_terms) => TermData::FunctionElim(
+                    go_box(head_term, Prec::Atomic),
+                    (input_terms.iter())
+                        .map(|input_term| go(input_term, Prec::Atomic))
+                        .collect(),
+                ),
+                TermData::RecordType(type_entries) => {
+                    TermData::RecordType(go_entries(type_entries))
+                }
+                TermData::RecordTerm(term_entries) => {
+                    TermData::RecordTerm(go_entries(term_entries))
+                }
+                TermData::RecordElim(head_term, label) => {
+                    TermData::RecordElim(go_box(head_term, Prec::Atomic), label.clone())
+                }
+                TermData::SequenceTerm(entry_terms) => TermData::SequenceTerm(
+                    (entry_terms.iter())
+                        .map(|entry_term| go(entry_term, Prec::Term))
+                        .collect(),
+                ),
+                term_data => term_data.clone(),
+            };
+
+            Term::new(term.location, term_data)
+        }
+
+        go(self, Prec::Term)
+    }
+
+    /// Collect the locations of the holes in a term, in the order that they
+    /// appear in the source code.
+    pub fn holes(&self) -> Vec<Location> {
+        fn go(term: &Term, holes: &mut Vec<Location>) {
+            match &term.data {
+                TermData::Name(_) => {}
+                TermData::Hole(_) => holes.push(term.location),
+                TermData::Paren(term) => go(term, holes),
+                TermData::Ann(term, r#type) => {
+                    go(term, holes);
+                    go(r#type, holes);
+                }
+                TermData::FunctionType(input_type_groups, output_type) => {
+                    for (_, input_type) in input_type_groups {
+                        go(input_type, holes);
+                    }
+                    go(output_type, holes);
+                }
+                TermData::FunctionArrowType(input_type, output_type) => {
+                    go(input_type, holes);
+                    go(output_type, holes);
+                }
+                TermData::FunctionTerm(inputs, output_term) => {
+                    for (_, input_type) in inputs {
+                        if let Some(input_type) = input_type {
+                            go(input_type, holes);
+                        }
+                    }
+                    go(output_term, holes);
+                }
+                TermData::FunctionElim(head_term, input_terms) => {
+                    go(head_term, holes);
+                    for input_term in input_terms {
+                        go(input_term, holes);
+                    }
+                }
+                TermData::RecordType(entries) | TermData::RecordTerm(entries) => {
+                    for (_, _, term) in entries {
+                        go(term, holes);
+                    }
+                }
+                TermData::RecordElim(head_term, _) => go(head_term, holes),
+                TermData::SequenceTerm(entry_terms) => {
+                    for entry_term in entry_terms {
+                        go(entry_term, holes);
+                    }
+                }
+                TermData::CharTerm(_)
+                | TermData::StringTerm(_)
+                | TermData::NumberTerm(_)
+                | TermData::Error => {}
+            }
+        }
+
+        let mut holes = Vec::new();
+        go(self, &mut holes);
+        holes
+    }
+}
+
+/// Display a term using the [surface pretty printer], which is easier to read
+/// than its `Debug` output.
+///
+/// [surface pretty printer]: crate::pass::surface_to_pretty
+impl fmt::Display for Term {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pretty_alloc = pretty::BoxAllocator;
+        let doc = crate::pass::surface_to_pretty::from_term(&pretty_alloc, self);
+        write!(f, "{}", doc.1.pretty(80))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recover_from_record_entry_error() {
+        let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+        let term = Term::from_str(0, "record { x = Type, y = ), z = Type }", &messages_tx);
+
+        match &term.data {
+            TermData::RecordTerm(entries) => {
+                let labels = entries.iter().map(|(label, _, _)| label.data.as_str());
+                assert_eq!(labels.collect::<Vec<_>>(), ["x", "y", "z"]);
+                assert!(matches!(entries[0].2.data, TermData::Name(_)));
+                assert!(matches!(entries[1].2.data, TermData::Error));
+                assert!(matches!(entries[2].2.data, TermData::Name(_)));
+            }
+            _ => panic!("expected a record term, found {:?}", term),
+        }
+        assert_eq!(messages_rx.try_iter().count(), 1);
+    }
+
+    #[test]
+    fn parse_holes() {
+        let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+
+        let term = Term::from_str(0, "?", &messages_tx);
+        assert!(matches!(term.data, TermData::Hole(None)));
+
+        let term = Term::from_str(0, "?goal-1", &messages_tx);
+        assert!(matches!(&term.data, TermData::Hole(Some(name)) if name == "goal-1"));
+
+        assert_eq!(messages_rx.try_iter().count(), 0);
+    }
+
+    #[test]
+    fn holes() {
+        let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+        let term = Term::from_str(0, "fun A a => record { x = ?x, y = [a, ?] }", &messages_tx);
+
+        let ranges = term.holes().into_iter().map(|location| match location {
+            Location::FileRange(_, range) => range.start..range.end,
+            Location::Generated => panic!("unexpected generated location"),
+        });
+        assert_eq!(ranges.collect::<Vec<_>>(), [24..26, 36..37]);
+        assert_eq!(messages_rx.try_iter().count(), 0);
+    }
+
+    fn parse_to_pretty(source: &str) -> String {
+        let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+        let term = Term::from_str(0, source, &messages_tx);
+        assert_eq!(messages_rx.try_iter().count(), 0, "errors in `{}`", source);
+
+        let pretty_alloc = pretty::BoxAllocator;
+        let doc = crate::pass::surface_to_pretty::from_term(&pretty_alloc, &term).1;
+        let output = doc.pretty(usize::MAX).to_string();
+        output
+    }
+
+    #[test]
+    fn trailing_commas() {
+        let sources = [
+            ("record { a = x, b = y }", "record { a = x, b = y, }"),
+            ("Record { a : A, b : B }", "Record { a : A, b : B, }"),
+            ("[a, b]", "[a, b,]"),
+            ("[a]", "[a,]"),
+        ];
+
+        for (source, trailing_source) in &sources {
+            assert_eq!(parse_to_pretty(source), parse_to_pretty(trailing_source));
+        }
+    }
+
+    #[test]
+    fn infix_function_elims() {
+        assert_eq!(parse_to_pretty("a `f` b"), parse_to_pretty("f a b"));
+        assert_eq!(parse_to_pretty("x y `f` z"), parse_to_pretty("f (x y) z"));
+        assert_eq!(
+            parse_to_pretty("a `f` b -> C"),
+            parse_to_pretty("f a b -> C")
+        );
+
+        let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+        let term = Term::from_str(0, "a `f` b `g` c", &messages_tx);
+        assert_eq!(messages_rx.try_iter().count(), 0);
+
+        match &term.data {
+            TermData::FunctionElim(head_term, input_terms) => {
+                assert!(matches!(&head_term.data, TermData::Name(name) if name == "g"));
+                assert!(matches!(input_terms[0].data, TermData::FunctionElim(_, _)));
+                assert!(matches!(&input_terms[1].data, TermData::Name(name) if name == "c"));
+            }
+            _ => panic!("expected a function elimination, found {:?}", term),
+        }
+    }
+
+    #[test]
+    fn annotation_precedence() {
+        let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+
+        let term = Term::from_str(0, "f x : T", &messages_tx);
+        match &term.data {
+            TermData::Ann(term, _) => assert!(matches!(term.data, TermData::FunctionElim(_, _))),
+            _ => panic!("expected an annotated term, found {:?}", term),
+        }
+
+        let term = Term::from_str(0, "f (x : T)", &messages_tx);
+        match &term.data {
+            TermData::FunctionElim(_, input_terms) => match input_terms.as_slice() {
+                [input_term] => match &input_term.strip_parens().data {
+                    TermData::Ann(term, _) => assert!(matches!(term.data, TermData::Name(_))),
+                    _ => panic!("expected an annotated input, found {:?}", input_term),
+                },
+                _ => panic!("expected a single input, found {:?}", input_terms),
+            },
+            _ => panic!("expected a function elimination, found {:?}", term),
+        }
+
+        assert_eq!(messages_rx.try_iter().count(), 0);
+    }
+
+    #[test]
+    fn display() {
+        let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+        let term = Term::from_str(0, "fun A a => (a : A)", &messages_tx);
+        assert_eq!(messages_rx.try_iter().count(), 0);
+
+        assert_eq!(term.to_string(), "fun A a => (a : A)");
+    }
+
+    #[test]
+    fn annotated_function_inputs() {
+        let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+        let term = Term::from_str(0, "fun (A B : Type) a => a", &messages_tx);
+        assert_eq!(messages_rx.try_iter().count(), 0);
+
+        match &term.data {
+            TermData::FunctionTerm(inputs, _) => {
+                let inputs = inputs.iter().map(|(input_name, input_type)| {
+                    (input_name.data.as_str(), input_type.is_some())
+                });
+                assert_eq!(
+                    inputs.collect::<Vec<_>>(),
+                    [("A", true), ("B", true), ("a", false)],
+                );
+            }
+            _ => panic!("expected a function term, found {:?}", term),
+        }
+
+        assert_eq!(term.to_string(), "fun (A : Type) (B : Type) a => a");
+    }
+
+    #[test]
+    fn reserved_words_as_names() {
+        use crate::reporting::{LexerError, Message};
+
+        let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+        Term::from_str(0, "fun let => let", &messages_tx);
+
+        let messages = messages_rx.try_iter().collect::<Vec<_>>();
+        match messages.as_slice() {
+            [Message::Lexer(LexerError::ReservedWord { word, .. }), ..] => assert_eq!(word, "let"),
+            messages => panic!("unexpected messages: {:?}", messages),
+        }
+    }
+
+    #[test]
+    fn mixed_function_type_chains() {
+        let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+        let term = Term::from_str(
+            0,
+            "Fun (a : Type) -> a -> Fun (b : Type) -> b",
+            &messages_tx,
+        );
+        assert_eq!(messages_rx.try_iter().count(), 0);
+
+        match &term.data {
+            TermData::FunctionType(_, output_type) => match &output_type.data {
+                TermData::FunctionArrowType(input_type, output_type) => {
+                    assert!(matches!(&input_type.data, TermData::Name(name) if name == "a"));
+                    assert!(matches!(output_type.data, TermData::FunctionType(_, _)));
+                }
+                _ => panic!("expected an arrow type, found {:?}", output_type),
+            },
+            _ => panic!("expected a function type, found {:?}", term),
+        }
+    }
+
+    #[test]
+    fn locations_exclude_comments() {
+        let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+        let term = Term::from_str(0, "-- leading\n  f x  -- trailing\n", &messages_tx);
+        assert_eq!(messages_rx.try_iter().count(), 0);
+
+        match term.location {
+            Location::FileRange(_, range) => assert_eq!(std::ops::Range::from(range), 13..16),
+            Location::Generated => panic!("expected a file range"),
+        }
+    }
+
+    #[test]
+    fn simplify_parens() {
+        use crate::pass::surface_to_pretty::{self, Parens};
+
+        let to_preserved_string = |term: &Term| {
+            let pretty_alloc = pretty::BoxAllocator;
+            let doc =
+                surface_to_pretty::from_term_with_parens(&pretty_alloc, Parens::Preserve, term).1;
+            let output = doc.pretty(usize::MAX).to_string();
+            output
+        };
+
+        let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+        let term = Term::from_str(0, "((x) (y))", &messages_tx).simplify();
+        match &term.data {
+            TermData::FunctionElim(head_term, input_terms) => {
+                assert!(matches!(&head_term.data, TermData::Name(name) if name == "x"));
+                match input_terms.as_slice() {
+                    [input_term] => {
+                        assert!(matches!(&input_term.data, TermData::Name(name) if name == "y"))
+                    }
+                    _ => panic!("expected a single input, found {:?}", input_terms),
+                }
+            }
+            _ => panic!("expected a function elimination, found {:?}", term),
+        }
+
+        let cases = [
+            ("((x) (y))", "x y"),
+            ("f (g x)", "f (g x)"),
+            ("(f) ((g) (x))", "f (g x)"),
+            ("(A -> B) -> (C)", "(A -> B) -> C"),
+            (
+                "((fun x => x) : (Type -> Type))",
+                "fun x => x : Type -> Type",
+            ),
+            ("[(x), (f x)]", "[x, f x]"),
+        ];
+        for (source, expected) in &cases {
+            let term = Term::from_str(0, source, &messages_tx).simplify();
+            assert_eq!(to_preserved_string(&term), *expected, "in `{}`", source);
+        }
+
+        assert_eq!(messages_rx.try_iter().count(), 0);
     }
 }
@@ -15,8 +15,15 @@ pub enum Token<'a> {
     StringLiteral(&'a str),
     #[regex(r"[-+]?[0-9][a-zA-Z0-9_\.]*")]
     NumericLiteral(&'a str),
-    #[regex(r"[a-zA-Z][a-zA-Z0-9\-]*")]
+    #[regex(r"_?[a-zA-Z][a-zA-Z0-9\-]*|_")]
     Name(&'a str),
+    #[regex(r"\?[a-zA-Z][a-zA-Z0-9\-]*", |lexer| &lexer.slice()[1..])]
+    NamedHole(&'a str),
+    #[regex(r"`[a-zA-Z][a-zA-Z0-9\-]*`", |lexer| {
+        let slice = lexer.slice();
+        &slice[1..slice.len() - 1]
+    })]
+    InfixName(&'a str),
 
     #[token("as")]
     As,
@@ -41,6 +48,8 @@ pub enum Token<'a> {
     Dot,
     #[token("=")]
     Equal,
+    #[token("?")]
+    Hole,
 
     #[token("(")]
     LParen,
@@ -58,6 +67,8 @@ pub enum Token<'a> {
     #[error]
     #[regex(r"\p{Whitespace}", logos::skip)]
     #[regex(r"--(.*)\n", logos::skip)]
+    // Underscores are only allowed at the start of names
+    #[regex(r"_?[a-zA-Z][a-zA-Z0-9\-]*_[a-zA-Z0-9\-_]*")]
     Error,
 }
 
@@ -69,6 +80,8 @@ impl<'a> fmt::Display for Token<'a> {
             Token::StringLiteral(s) => write!(f, "{}", s),
             Token::NumericLiteral(s) => write!(f, "{}", s),
             Token::Name(s) => write!(f, "{}", s),
+            Token::NamedHole(s) => write!(f, "?{}", s),
+            Token::InfixName(s) => write!(f, "`{}`", s),
 
             Token::As => write!(f, "as"),
             Token::FunTerm => write!(f, "fun"),
@@ -82,6 +95,7 @@ impl<'a> fmt::Display for Token<'a> {
             Token::Arrow => write!(f, "->"),
             Token::Equal => write!(f, "="),
             Token::Dot => write!(f, "."),
+            Token::Hole => write!(f, "?"),
 
             Token::LParen => write!(f, "("),
             Token::RParen => write!(f, ")"),
@@ -95,6 +109,12 @@ impl<'a> fmt::Display for Token<'a> {
     }
 }
 
+/// Words that are reserved for future use as keywords, and so cannot be
+/// used as names.
+pub const RESERVED_WORDS: &[&str] = &[
+    "else", "forall", "if", "import", "in", "let", "match", "module", "then", "where",
+];
+
 pub type Spanned<Tok, Loc, Error> = Result<(Loc, Tok, Loc), Error>;
 
 pub fn tokens(
@@ -107,6 +127,10 @@ pub fn tokens(
             Token::Error => Err(LexerError::InvalidToken {
                 location: Location::file_range(file_id, range),
             }),
+            Token::Name(name) if RESERVED_WORDS.contains(&name) => Err(LexerError::ReservedWord {
+                location: Location::file_range(file_id, range),
+                word: name.to_owned(),
+            }),
             token => Ok((range.start, token, range.end)),
         })
 }
@@ -119,3 +143,38 @@ fn behavior_after_error() {
     let result: Vec<_> = from_lex.iter().map(Result::is_ok).collect();
     assert_eq!(result, vec![false, true]);
 }
+
+#[test]
+fn reserved_words() {
+    let from_lex: Vec<_> = tokens(0, "fun let => let").collect();
+    let reserved_words = from_lex.iter().filter_map(|token| match token {
+        Err(LexerError::ReservedWord { word, .. }) => Some(word.as_str()),
+        _ => None,
+    });
+    assert_eq!(reserved_words.collect::<Vec<_>>(), ["let", "let"]);
+
+    let from_lex: Vec<_> = tokens(0, "fun letter => letter").collect();
+    assert!(from_lex.iter().all(Result::is_ok));
+}
+
+#[test]
+fn underscore_prefixed_names() {
+    let names = |source| {
+        let from_lex: Vec<_> = tokens(0, source).collect();
+        let names = from_lex.iter().filter_map(|token| match token {
+            Ok((_, Token::Name(name), _)) => Some(*name),
+            _ => None,
+        });
+        names.collect::<Vec<_>>()
+    };
+
+    assert_eq!(names("_ _a _unused-input"), ["_", "_a", "_unused-input"]);
+
+    // Underscores are only allowed at the start of names
+    let from_lex: Vec<_> = tokens(0, "a_b _a_ a-b_").collect();
+    let invalid = from_lex
+        .iter()
+        .filter(|token| matches!(token, Err(LexerError::InvalidToken { .. })));
+    assert_eq!(invalid.count(), 3);
+    assert_eq!(from_lex.len(), 3);
+}
@@ -6,6 +6,7 @@
 #![allow(clippy::while_let_on_iterator)]
 
 pub mod lang;
+pub mod library;
 pub mod pass;
 
 mod literal;
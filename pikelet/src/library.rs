@@ -0,0 +1,165 @@
+//! The standard library.
+//!
+//! The parsed and elaborated forms of the library are computed lazily, and
+//! cached so that they are only computed once for each [`Prelude`]. The
+//! prelude returned by [`prelude`] is shared by the whole process, so its
+//! forms are only computed once per process.
+
+use codespan_reporting::diagnostic::Diagnostic;
+use once_cell::sync::OnceCell;
+
+use crate::lang::{core, surface, FileId};
+use crate::pass::surface_to_core;
+
+/// The source code of the prelude.
+pub const PRELUDE: &str = include_str!("../../examples/prelude.pi");
+
+/// The file id used in the locations of the [process-wide prelude][prelude].
+///
+/// This is chosen so that it is unlikely to clash with the file ids used by
+/// embedders. Use [`Prelude::new`] to create a prelude with a different file
+/// id, for example one that was registered with a file database so that
+/// diagnostics can point into the prelude.
+pub const PRELUDE_FILE_ID: FileId = FileId::MAX;
+
+/// The prelude shared by the whole process, using [`PRELUDE_FILE_ID`] as its
+/// file id.
+pub fn prelude() -> &'static Prelude {
+    static PRELUDE: OnceCell<Prelude> = OnceCell::new();
+
+    PRELUDE.get_or_init(|| Prelude::new(PRELUDE_FILE_ID))
+}
+
+/// The [process-wide prelude][prelude], parsed into the [surface language].
+///
+/// [surface language]: crate::lang::surface
+pub fn prelude_surface() -> Result<&'static surface::Term, &'static [Diagnostic<FileId>]> {
+    prelude().surface()
+}
+
+/// The [process-wide prelude][prelude], elaborated into the [core language].
+///
+/// [core language]: crate::lang::core
+pub fn prelude_core() -> Result<&'static core::Term, &'static [Diagnostic<FileId>]> {
+    prelude().core()
+}
+
+/// The prelude, along with its lazily computed parsed and elaborated forms.
+pub struct Prelude {
+    file_id: FileId,
+    surface: OnceCell<Result<surface::Term, Vec<Diagnostic<FileId>>>>,
+    core: OnceCell<Result<core::Term, Vec<Diagnostic<FileId>>>>,
+}
+
+impl Prelude {
+    /// Create a prelude, using the given file id in the locations of the
+    /// parsed and elaborated terms, and in any diagnostics.
+    pub fn new(file_id: FileId) -> Prelude {
+        Prelude {
+            file_id,
+            surface: OnceCell::new(),
+            core: OnceCell::new(),
+        }
+    }
+
+    /// The file id of the prelude.
+    pub fn file_id(&self) -> FileId {
+        self.file_id
+    }
+
+    /// The prelude, parsed into the [surface language], or the diagnostics
+    /// that were produced if it contains syntax errors.
+    ///
+    /// [surface language]: crate::lang::surface
+    pub fn surface(&self) -> Result<&surface::Term, &[Diagnostic<FileId>]> {
+        let result = self.surface.get_or_init(|| {
+            let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+            let term = surface::Term::from_str(self.file_id, PRELUDE, &messages_tx);
+            surface_to_core::into_result(&messages_rx, term)
+        });
+
+        result.as_ref().map_err(Vec::as_slice)
+    }
+
+    /// The prelude, elaborated into the [core language] using the default
+    /// [globals], or the diagnostics that were produced if it contains errors.
+    ///
+    /// [core language]: crate::lang::core
+    /// [globals]: crate::lang::core::Globals
+    pub fn core(&self) -> Result<&core::Term, &[Diagnostic<FileId>]> {
+        let result = self.core.get_or_init(|| {
+            let surface_term = self.surface().map_err(<[_]>::to_vec)?;
+            let globals = core::Globals::default();
+            let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+            let mut state = surface_to_core::State::new(&globals, messages_tx);
+            let (term, _) = state.synth_type(surface_term);
+            surface_to_core::into_result(&messages_rx, term)
+        });
+
+        result.as_ref().map_err(Vec::as_slice)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::Location;
+
+    #[test]
+    fn prelude_is_cached() {
+        let prelude = Prelude::new(0);
+
+        assert!(std::ptr::eq(
+            prelude.surface().unwrap(),
+            prelude.surface().unwrap(),
+        ));
+        assert!(std::ptr::eq(
+            prelude.core().unwrap(),
+            prelude.core().unwrap()
+        ));
+    }
+
+    #[test]
+    fn process_wide_prelude_is_cached() {
+        assert!(std::ptr::eq(prelude(), prelude()));
+        assert_eq!(prelude().file_id(), PRELUDE_FILE_ID);
+
+        assert!(std::ptr::eq(
+            prelude_surface().unwrap(),
+            prelude_surface().unwrap(),
+        ));
+        assert!(std::ptr::eq(
+            prelude_core().unwrap(),
+            prelude_core().unwrap()
+        ));
+    }
+
+    #[test]
+    fn prelude_is_an_annotated_record() {
+        let prelude = Prelude::new(0);
+
+        assert!(matches!(
+            &prelude.surface().unwrap().data,
+            surface::TermData::Ann(term, _) if matches!(term.data, surface::TermData::RecordTerm(_)),
+        ));
+        assert!(matches!(
+            &prelude.core().unwrap().data,
+            core::TermData::Ann(term, _) if matches!(term.data, core::TermData::RecordTerm(_, _)),
+        ));
+    }
+
+    #[test]
+    fn prelude_uses_file_id() {
+        let prelude = Prelude::new(42);
+
+        assert_eq!(prelude.file_id(), 42);
+        assert!(matches!(
+            prelude.surface().unwrap().location,
+            Location::FileRange(42, _),
+        ));
+        assert!(matches!(
+            prelude.core().unwrap().location,
+            Location::FileRange(42, _),
+        ));
+    }
+}
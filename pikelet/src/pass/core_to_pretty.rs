@@ -175,6 +175,8 @@ where
     }
 }
 
+/// Pretty print a constant using the same syntax as literals in the surface
+/// language, escaping special characters in character and string constants.
 pub fn from_constant<'a, D>(alloc: &'a D, constant: &'a Constant) -> DocBuilder<'a, D>
 where
     D: DocAllocator<'a>,
@@ -207,3 +209,91 @@ where
         doc
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::Location;
+    use crate::literal;
+
+    fn print_constant(constant: &Constant) -> String {
+        let pretty_alloc = pretty::BoxAllocator;
+        let doc = from_constant(&pretty_alloc, constant).1;
+        let output = doc.pretty(usize::MAX).to_string();
+        output
+    }
+
+    fn parse_constant(like: &Constant, source: &str) -> Option<Constant> {
+        let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+        let state = literal::State::new(Location::generated(), source, &messages_tx);
+        let constant = match like {
+            Constant::U8(_) => state.number_to_unsigned_int().map(Constant::U8),
+            Constant::U16(_) => state.number_to_unsigned_int().map(Constant::U16),
+            Constant::U32(_) => state.number_to_unsigned_int().map(Constant::U32),
+            Constant::U64(_) => state.number_to_unsigned_int().map(Constant::U64),
+            Constant::S8(_) => state.number_to_signed_int().map(Constant::S8),
+            Constant::S16(_) => state.number_to_signed_int().map(Constant::S16),
+            Constant::S32(_) => state.number_to_signed_int().map(Constant::S32),
+            Constant::S64(_) => state.number_to_signed_int().map(Constant::S64),
+            Constant::F32(_) => state.number_to_float().map(Constant::F32),
+            Constant::F64(_) => state.number_to_float().map(Constant::F64),
+            Constant::Char(_) => state.quoted_to_unicode_char().map(Constant::Char),
+            Constant::String(_) => state.quoted_to_utf8_string().map(Constant::String),
+        };
+        assert_eq!(
+            messages_rx.try_iter().count(),
+            0,
+            "messages for `{}`",
+            source
+        );
+        constant
+    }
+
+    #[test]
+    fn constants_reparse() {
+        let constants = vec![
+            Constant::U8(u8::MAX),
+            Constant::U16(u16::MAX),
+            Constant::U32(u32::MAX),
+            Constant::U64(u64::MAX),
+            Constant::S8(i8::MIN),
+            Constant::S16(i16::MIN),
+            Constant::S32(i32::MIN),
+            Constant::S64(i64::MIN),
+            Constant::F32(1.5),
+            Constant::F64(-0.25),
+            Constant::Char('a'),
+            Constant::Char('\''),
+            Constant::Char('\\'),
+            Constant::Char('\n'),
+            Constant::Char('\0'),
+            Constant::Char('\u{7f}'),
+            Constant::String(String::new()),
+            Constant::String("hello world".to_owned()),
+            Constant::String("\"quoted\"\t'single'\r\n".to_owned()),
+            Constant::String("back\\slash \u{1b}[0m".to_owned()),
+        ];
+
+        for constant in &constants {
+            let source = print_constant(constant);
+            assert_eq!(
+                parse_constant(constant, &source).as_ref(),
+                Some(constant),
+                "`{}` did not re-parse to the same constant",
+                source,
+            );
+        }
+    }
+
+    #[test]
+    fn constants_print_like_literals() {
+        assert_eq!(print_constant(&Constant::U8(255)), "255");
+        assert_eq!(print_constant(&Constant::S8(-128)), "-128");
+        assert_eq!(print_constant(&Constant::Char('a')), "'a'");
+        assert_eq!(print_constant(&Constant::Char('\'')), "'\\''");
+        assert_eq!(
+            print_constant(&Constant::String("a\"b".to_owned())),
+            "\"a\\\"b\""
+        );
+    }
+}
@@ -151,20 +151,20 @@ impl<'me> State<'me> {
                 let mut current_output_term = output_term;
 
                 let fresh_input_name = self.push_name(Some(input_name_hint));
-                let mut input_names = vec![Located::generated(fresh_input_name)];
+                let mut inputs = vec![(Located::generated(fresh_input_name), None)];
 
                 while let TermData::FunctionTerm(input_name_hint, output_term) =
                     &current_output_term.data
                 {
                     let fresh_input_name = self.push_name(Some(input_name_hint));
-                    input_names.push(Located::generated(fresh_input_name));
+                    inputs.push((Located::generated(fresh_input_name), None));
                     current_output_term = output_term;
                 }
 
                 let output_term = self.from_term(current_output_term);
-                self.pop_many_names(input_names.len());
+                self.pop_many_names(inputs.len());
 
-                surface::TermData::FunctionTerm(input_names, Box::new(output_term))
+                surface::TermData::FunctionTerm(inputs, Box::new(output_term))
             }
             TermData::FunctionElim(head_term, input_term) => {
                 let mut current_head_term = head_term;
@@ -5,32 +5,76 @@
 //! [surface language]: crate::lang::surface
 //! [core language]: crate::lang::core
 
+use codespan_reporting::diagnostic::{Diagnostic, Severity};
 use contracts::debug_ensures;
-use crossbeam_channel::Sender;
+use crossbeam_channel::{Receiver, Sender};
 use num_traits::{Float, PrimInt, Signed, Unsigned};
+use std::fmt;
 use std::sync::Arc;
 
 use crate::lang::core::semantics::{self, Elim, RecordClosure, Unfold, Value};
-use crate::lang::surface::{Term, TermData};
-use crate::lang::{core, Location};
+use crate::lang::surface::{FunctionInput, Term, TermData};
+use crate::lang::{core, FileId, Located, Location};
 use crate::literal;
-use crate::pass::core_to_surface;
+use crate::pass::{core_to_surface, surface_to_pretty};
 use crate::reporting::{AmbiguousTerm, ExpectedType, Message, SurfaceToCoreMessage};
 
+pub use crate::lang::core::typing::DEFAULT_RECURSION_LIMIT;
+
 /// The state of the elaborator.
 pub struct State<'me> {
     /// Global definition environment.
     globals: &'me core::Globals,
     /// Local type environment (used for getting the types of local variables).
-    local_declarations: core::Locals<(Option<String>, Arc<Value>)>,
+    local_declarations: core::Locals<(Option<Located<String>>, Arc<Value>)>,
     /// Local value environment (used for evaluation).
     local_definitions: core::Locals<Arc<Value>>,
     /// Distillation state (used for pretty printing).
     core_to_surface: core_to_surface::State<'me>,
+    /// Whether binding a name that is already in scope should be reported as
+    /// an error.
+    strict_shadowing: bool,
+    /// The current nesting depth of the elaborator.
+    depth: usize,
+    /// The maximum nesting depth of the elaborator.
+    recursion_limit: usize,
+    /// Whether to record the edits made to terms during elaboration.
+    record_edits: bool,
+    /// The edits made to terms during elaboration.
+    edits: Vec<Edit>,
     /// The diagnostic messages accumulated during elaboration.
     message_tx: Sender<Message>,
 }
 
+/// An edit made to a term during elaboration, recording information that
+/// was filled in from the expected type.
+#[derive(Clone, Debug)]
+pub enum Edit {
+    /// The annotation of an unannotated function input was filled in from
+    /// the input type of the expected function type.
+    FilledInputAnnotation {
+        input_name: Located<String>,
+        r#type: Term,
+    },
+}
+
+impl fmt::Display for Edit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Edit::FilledInputAnnotation { input_name, r#type } => {
+                let pretty_alloc = pretty::BoxAllocator;
+                let r#type = surface_to_pretty::from_term(&pretty_alloc, r#type).1;
+                write!(
+                    f,
+                    "filled annotation of input `{}` with `{}`",
+                    input_name.data,
+                    r#type.pretty(usize::MAX),
+                )
+            }
+        }
+    }
+}
+
 impl<'me> State<'me> {
     /// Construct a new elaborator state.
     pub fn new(globals: &'me core::Globals, message_tx: Sender<Message>) -> State<'me> {
@@ -39,35 +83,100 @@ impl<'me> State<'me> {
             local_declarations: core::Locals::new(),
             local_definitions: core::Locals::new(),
             core_to_surface: core_to_surface::State::new(globals),
+            strict_shadowing: false,
+            depth: 0,
+            recursion_limit: DEFAULT_RECURSION_LIMIT,
+            record_edits: false,
+            edits: Vec::new(),
             message_tx,
         }
     }
 
+    /// Set whether binding a name that is already in scope should be reported
+    /// as an error. This is disabled by default.
+    pub fn set_strict_shadowing(&mut self, strict_shadowing: bool) {
+        self.strict_shadowing = strict_shadowing;
+    }
+
+    /// Set the maximum nesting depth of terms that the elaborator will descend
+    /// into before reporting an error. This guards against stack overflows
+    /// when elaborating untrusted input. Defaults to [`DEFAULT_RECURSION_LIMIT`].
+    pub fn set_recursion_limit(&mut self, recursion_limit: usize) {
+        self.recursion_limit = recursion_limit;
+    }
+
+    /// Set whether the edits made to terms during elaboration should be
+    /// recorded. This is disabled by default.
+    pub fn set_record_edits(&mut self, record_edits: bool) {
+        self.record_edits = record_edits;
+    }
+
+    /// Take the edits made to terms during elaboration that have been
+    /// recorded so far.
+    pub fn take_edits(&mut self) -> Vec<Edit> {
+        std::mem::take(&mut self.edits)
+    }
+
     /// Get the size of the local environment.
     fn size(&self) -> core::LocalSize {
         self.local_definitions.size()
     }
 
     /// Get a local entry.
-    fn get_local(&self, name: &str) -> Option<(core::LocalIndex, &Arc<Value>)> {
+    fn get_local(&self, name: &str) -> Option<(core::LocalIndex, &Located<String>, &Arc<Value>)> {
         for (local_index, (decl_name, r#type)) in self.local_declarations.iter_rev() {
-            if decl_name.as_ref().map_or(false, |n| n == name) {
-                return Some((local_index, r#type));
+            match decl_name {
+                Some(decl_name) if decl_name.data == name => {
+                    return Some((local_index, decl_name, r#type));
+                }
+                Some(_) | None => {}
             }
         }
         None
     }
 
+    /// Find the name of the closest local entry that is spelled similarly to
+    /// the given name, for suggesting in diagnostics.
+    fn get_similar_local_name(&self, name: &str) -> Option<&Located<String>> {
+        let max_distance = std::cmp::max(1, name.chars().count() / 3);
+
+        (self.local_declarations.iter_rev())
+            .filter_map(|(_, (decl_name, _))| decl_name.as_ref())
+            .map(|decl_name| (edit_distance(name, &decl_name.data), decl_name))
+            .filter(|(distance, _)| *distance <= max_distance)
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, decl_name)| decl_name)
+    }
+
     /// Push a local entry.
-    fn push_local(&mut self, name: Option<&str>, value: Arc<Value>, r#type: Arc<Value>) {
-        self.local_declarations
-            .push((name.map(str::to_owned), r#type));
+    fn push_local(
+        &mut self,
+        name: Option<&Located<String>>,
+        value: Arc<Value>,
+        r#type: Arc<Value>,
+    ) {
+        if let (true, Some(name)) = (self.strict_shadowing, name) {
+            if let Some((_, outer_name, _)) = self.get_local(&name.data) {
+                self.report(SurfaceToCoreMessage::ShadowedName {
+                    name: name.data.clone(),
+                    outer_location: outer_name.location,
+                    inner_location: name.location,
+                });
+            }
+        }
+
+        self.local_declarations.push((name.cloned(), r#type));
         self.local_definitions.push(value);
-        self.core_to_surface.push_name(name);
+        self.core_to_surface
+            .push_name(name.map(|name| name.data.as_str()));
     }
 
     /// Push a local parameter.
-    fn push_local_param(&mut self, name: Option<&str>, r#type: Arc<Value>) -> Arc<Value> {
+    fn push_local_param(
+        &mut self,
+        name: Option<&Located<String>>,
+        r#type: Arc<Value>,
+    ) -> Arc<Value> {
         let value = Arc::new(Value::local(self.size().next_level(), []));
         self.push_local(name, value.clone(), r#type);
         value
@@ -87,6 +196,23 @@ impl<'me> State<'me> {
         self.core_to_surface.pop_many_names(count);
     }
 
+    /// Run `f` one level deeper in the elaborator, returning `None` and
+    /// reporting an error if this would exceed the recursion limit.
+    fn nested<T>(&mut self, location: Location, f: impl FnOnce(&mut Self) -> T) -> Option<T> {
+        if self.depth >= self.recursion_limit {
+            self.report(SurfaceToCoreMessage::RecursionLimitExceeded {
+                location,
+                limit: self.recursion_limit,
+            });
+            return None;
+        }
+
+        self.depth += 1;
+        let result = f(self);
+        self.depth -= 1;
+        Some(result)
+    }
+
     /// Report a diagnostic message.
     fn report(&self, error: SurfaceToCoreMessage) {
         self.message_tx.send(error.into()).unwrap();
@@ -118,9 +244,23 @@ impl<'me> State<'me> {
 
     /// Fully normalize a [`core::Term`] using [normalization by evaluation].
     ///
+    /// An error is reported instead if the term is [nested more deeply] than
+    /// the recursion limit. Only the syntax of the term is checked, so this
+    /// does not account for the depth of any unfolded global definitions.
+    ///
+    /// [nested more deeply]: crate::lang::core::Term::depth
+    ///
     /// [`core::Term`]: crate::lang::core::Term
     /// [normalization by evaluation]: https://en.wikipedia.org/wiki/Normalisation_by_evaluation
     pub fn normalize(&mut self, term: &core::Term) -> core::Term {
+        if term.depth() > self.recursion_limit {
+            self.report(SurfaceToCoreMessage::RecursionLimitExceeded {
+                location: term.location,
+                limit: self.recursion_limit,
+            });
+            return core::Term::new(term.location, core::TermData::Error);
+        }
+
         semantics::normalize(self.globals, &mut self.local_definitions, term)
     }
 
@@ -175,17 +315,53 @@ impl<'me> State<'me> {
     #[debug_ensures(self.local_declarations.size() == old(self.local_declarations.size()))]
     #[debug_ensures(self.local_definitions.size() == old(self.local_definitions.size()))]
     pub fn is_type(&mut self, term: &Term) -> Option<core::Term> {
+        self.is_type_or_report(term, |location, found_type| {
+            SurfaceToCoreMessage::MismatchedTypes {
+                location,
+                found_type,
+                expected_type: ExpectedType::Universe,
+            }
+        })
+    }
+
+    /// Check that the input of a function type is a type, and return the
+    /// elaborated term.
+    fn is_function_input_type(&mut self, term: &Term) -> Option<core::Term> {
+        self.is_type_or_report(term, |location, found_type| {
+            SurfaceToCoreMessage::FunctionInputNotAType {
+                location,
+                found_type,
+            }
+        })
+    }
+
+    /// Check that the output of a function type is a type, and return the
+    /// elaborated term.
+    fn is_function_output_type(&mut self, term: &Term) -> Option<core::Term> {
+        self.is_type_or_report(term, |location, found_type| {
+            SurfaceToCoreMessage::FunctionOutputNotAType {
+                location,
+                found_type,
+            }
+        })
+    }
+
+    /// Check that a term is a type, and return the elaborated term, using
+    /// `on_mismatch` to construct the message reported if it is not.
+    #[debug_ensures(self.local_declarations.size() == old(self.local_declarations.size()))]
+    #[debug_ensures(self.local_definitions.size() == old(self.local_definitions.size()))]
+    fn is_type_or_report(
+        &mut self,
+        term: &Term,
+        on_mismatch: impl FnOnce(Location, Term) -> SurfaceToCoreMessage,
+    ) -> Option<core::Term> {
         let (core_term, r#type) = self.synth_type(term);
         match r#type.force(self.globals) {
             Value::TypeType => Some(core_term),
             Value::Error => Some(core::Term::new(term.location, core::TermData::Error)),
             found_type => {
                 let found_type = self.read_back_to_surface(&found_type);
-                self.report(SurfaceToCoreMessage::MismatchedTypes {
-                    location: term.location,
-                    found_type,
-                    expected_type: ExpectedType::Universe,
-                });
+                self.report(on_mismatch(term.location, found_type));
                 None
             }
         }
@@ -195,19 +371,70 @@ impl<'me> State<'me> {
     #[debug_ensures(self.local_declarations.size() == old(self.local_declarations.size()))]
     #[debug_ensures(self.local_definitions.size() == old(self.local_definitions.size()))]
     pub fn check_type(&mut self, term: &Term, expected_type: &Arc<Value>) -> core::Term {
+        self.nested(term.location, |state| {
+            state.check_type_unguarded(term, expected_type)
+        })
+        .unwrap_or_else(|| core::Term::new(term.location, core::TermData::Error))
+    }
+
+    fn check_type_unguarded(&mut self, term: &Term, expected_type: &Arc<Value>) -> core::Term {
         match (&term.data, expected_type.force(self.globals)) {
             (_, Value::Error) => core::Term::new(term.location, core::TermData::Error),
 
-            (TermData::FunctionTerm(input_names, output_term), _) => {
+            (TermData::Hole(name), _) => {
+                let expected_type = self.read_back_to_surface(expected_type);
+                self.report(SurfaceToCoreMessage::Hole {
+                    location: term.location,
+                    name: name.clone(),
+                    expected_type: Some(expected_type),
+                });
+                core::Term::new(term.location, core::TermData::Error)
+            }
+
+            (TermData::Paren(term), _) => self.check_type(term, expected_type),
+
+            (TermData::FunctionElim(head_term, input_terms), _) => {
+                match &head_term.strip_parens().data {
+                    // Immediately applied function terms, eg. `(fun x => x) Type`
+                    TermData::FunctionTerm(inputs, output_term)
+                        if inputs.len() == input_terms.len()
+                            && inputs.iter().all(|(_, input_type)| input_type.is_none()) =>
+                    {
+                        self.check_function_redex(
+                            term.location,
+                            inputs,
+                            output_term,
+                            input_terms,
+                            expected_type,
+                        )
+                    }
+                    _ => self.check_synth_type(term, expected_type),
+                }
+            }
+
+            (TermData::FunctionTerm(inputs, output_term), _) => {
                 let mut seen_input_count = 0;
                 let mut expected_type = expected_type.clone();
-                let mut pending_input_names = input_names.iter();
+                let mut pending_inputs = inputs.iter();
 
-                while let Some(input_name) = pending_input_names.next() {
+                while let Some((input_name, input_annotation)) = pending_inputs.next() {
                     match expected_type.force(self.globals) {
                         Value::FunctionType(_, input_type, output_closure) => {
+                            match input_annotation {
+                                Some(input_annotation) => {
+                                    self.check_input_annotation(input_annotation, input_type)
+                                }
+                                None if self.record_edits => {
+                                    let r#type = self.read_back_to_surface(input_type);
+                                    self.edits.push(Edit::FilledInputAnnotation {
+                                        input_name: input_name.clone(),
+                                        r#type,
+                                    });
+                                }
+                                None => {}
+                            }
                             let input_value =
-                                self.push_local_param(Some(&input_name.data), input_type.clone());
+                                self.push_local_param(Some(input_name), input_type.clone());
                             seen_input_count += 1;
                             expected_type = output_closure.apply(self.globals, input_value);
                         }
@@ -219,7 +446,7 @@ impl<'me> State<'me> {
                             self.report(SurfaceToCoreMessage::TooManyInputsInFunctionTerm {
                                 unexpected_inputs: std::iter::once(input_name.location)
                                     .chain(
-                                        pending_input_names.map(|input_name| input_name.location),
+                                        pending_inputs.map(|(input_name, _)| input_name.location),
                                     )
                                     .collect(),
                             });
@@ -232,7 +459,7 @@ impl<'me> State<'me> {
 
                 let core_output_term = self.check_type(output_term, &expected_type);
                 self.pop_many_locals(seen_input_count);
-                (input_names.iter().rev()).fold(core_output_term, |core_output_term, input_name| {
+                (inputs.iter().rev()).fold(core_output_term, |core_output_term, (input_name, _)| {
                     core::Term::new(
                         Location::merge(input_name.location, core_output_term.location),
                         core::TermData::FunctionTerm(
@@ -244,7 +471,30 @@ impl<'me> State<'me> {
             }
 
             (TermData::RecordTerm(term_entries), Value::RecordType(type_labels, closure)) => {
-                let mut pending_entries = term_entries.iter();
+                use std::collections::btree_map::{BTreeMap, Entry};
+
+                let mut duplicate_labels = Vec::new();
+                let mut seen_labels = BTreeMap::new();
+                let mut unique_entries = Vec::with_capacity(term_entries.len());
+
+                for entry @ (label, _, _) in term_entries {
+                    match seen_labels.entry(label.data.as_str()) {
+                        Entry::Vacant(seen_entry) => {
+                            seen_entry.insert(label.location);
+                            unique_entries.push(entry);
+                        }
+                        Entry::Occupied(seen_entry) => {
+                            let seen_location = *seen_entry.get();
+                            duplicate_labels.push((
+                                label.data.clone(),
+                                seen_location,
+                                label.location,
+                            ));
+                        }
+                    }
+                }
+
+                let mut pending_entries = unique_entries.into_iter();
                 let mut pending_type_labels = type_labels.iter();
                 let mut core_terms = Vec::with_capacity(pending_entries.len());
 
@@ -258,8 +508,9 @@ impl<'me> State<'me> {
                                 let name = name.as_ref().unwrap_or(next_label);
                                 let core_term = self.check_type(term, &r#type);
                                 let core_value = self.eval(&core_term);
+                                self.check_ignored_inputs(next_label, term.location, &core_term);
 
-                                self.push_local(Some(&name.data), core_value.clone(), r#type);
+                                self.push_local(Some(name), core_value.clone(), r#type);
                                 core_terms.push(Arc::new(core_term));
 
                                 return core_value;
@@ -275,9 +526,13 @@ impl<'me> State<'me> {
                 self.pop_many_locals(core_terms.len());
                 unexpected_labels.extend(pending_entries.map(|(label, _, _)| label.location));
 
-                if !missing_labels.is_empty() || !unexpected_labels.is_empty() {
+                if !duplicate_labels.is_empty()
+                    || !missing_labels.is_empty()
+                    || !unexpected_labels.is_empty()
+                {
                     self.report(SurfaceToCoreMessage::InvalidRecordTerm {
                         location: term.location,
+                        duplicate_labels,
                         missing_labels,
                         unexpected_labels,
                     });
@@ -380,19 +635,141 @@ impl<'me> State<'me> {
                 }
             },
 
-            (_, _) => match self.synth_type(term) {
-                (term, found_type) if self.is_equal(&found_type, expected_type) => term,
-                (_, found_type) => {
-                    let found_type = self.read_back_to_surface(&found_type);
-                    let expected_type = self.read_back_to_surface(expected_type);
-                    self.report(SurfaceToCoreMessage::MismatchedTypes {
-                        location: term.location,
-                        found_type,
-                        expected_type: ExpectedType::Type(expected_type),
-                    });
-                    core::Term::new(term.location, core::TermData::Error)
-                }
-            },
+            (_, _) => self.check_synth_type(term, expected_type),
+        }
+    }
+
+    /// Check a term by synthesizing its type, and comparing it with the
+    /// expected type.
+    fn check_synth_type(&mut self, term: &Term, expected_type: &Arc<Value>) -> core::Term {
+        match self.synth_type(term) {
+            (term, found_type) if self.is_equal(&found_type, expected_type) => term,
+            (_, found_type) => {
+                let found_type = self.read_back_to_surface(&found_type);
+                let expected_type = self.read_back_to_surface(expected_type);
+                self.report(SurfaceToCoreMessage::MismatchedTypes {
+                    location: term.location,
+                    found_type,
+                    expected_type: ExpectedType::Type(expected_type),
+                });
+                core::Term::new(term.location, core::TermData::Error)
+            }
+        }
+    }
+
+    /// Check an immediately applied function term against an expected type.
+    ///
+    /// The redex is beta-reduced: the inputs are elaborated, and are then
+    /// bound as local definitions while the body of the function is checked
+    /// against the expected type. This allows us to check terms like
+    /// `(fun x => x) Type`, where the function term would otherwise be
+    /// ambiguous, and lets the body depend on the values of the inputs. The
+    /// elaborated term is the body with the inputs substituted into it.
+    #[debug_ensures(self.local_declarations.size() == old(self.local_declarations.size()))]
+    #[debug_ensures(self.local_definitions.size() == old(self.local_definitions.size()))]
+    fn check_function_redex(
+        &mut self,
+        location: Location,
+        inputs: &[FunctionInput],
+        output_term: &Term,
+        input_terms: &[Term],
+        expected_type: &Arc<Value>,
+    ) -> core::Term {
+        let input_entries = (input_terms.iter())
+            .map(|input_term| {
+                let (core_input, input_type) = self.synth_type(input_term);
+                (self.eval(&core_input), input_type)
+            })
+            .collect::<Vec<_>>();
+
+        for ((input_name, _), (input_value, input_type)) in
+            Iterator::zip(inputs.iter(), input_entries)
+        {
+            self.push_local(Some(input_name), input_value, input_type);
+        }
+
+        let core_output_term = self.check_type(output_term, expected_type);
+        let output_value = self.eval(&core_output_term);
+        self.pop_many_locals(inputs.len());
+
+        core::Term::new(location, self.read_back(&output_value).data)
+    }
+
+    /// Synthesize the type of a function term where every input has been
+    /// annotated with a type, eg. `fun (A : Type) (a : A) => a`.
+    ///
+    /// The function term is annotated in the elaborated term, so that its type
+    /// can be synthesized by the core type checker.
+    #[debug_ensures(self.local_declarations.size() == old(self.local_declarations.size()))]
+    #[debug_ensures(self.local_definitions.size() == old(self.local_definitions.size()))]
+    fn synth_function_term(
+        &mut self,
+        location: Location,
+        inputs: &[(&Located<String>, &Term)],
+        output_term: &Term,
+    ) -> (core::Term, Arc<Value>) {
+        let mut core_input_types = Vec::with_capacity(inputs.len());
+        for (input_name, input_type) in inputs {
+            let core_input_type = self
+                .is_type(input_type)
+                .unwrap_or_else(|| core::Term::new(input_type.location, core::TermData::Error));
+            let input_type = self.eval(&core_input_type);
+            self.push_local_param(Some(input_name), input_type);
+            core_input_types.push(core_input_type);
+        }
+
+        let (core_output_term, output_type) = self.synth_type(output_term);
+        let core_output_type = self.read_back(&output_type);
+        self.pop_many_locals(inputs.len());
+
+        let (core_function_term, core_function_type) =
+            Iterator::zip(inputs.iter().rev(), core_input_types.into_iter().rev()).fold(
+                (core_output_term, core_output_type),
+                |(core_output_term, core_output_type), ((input_name, _), core_input_type)| {
+                    let core_function_term = core::TermData::FunctionTerm(
+                        input_name.data.clone(),
+                        Arc::new(core_output_term),
+                    );
+                    // Only name the inputs that the output type depends on, so
+                    // that types like `Type -> Type` are read back as arrows.
+                    let input_name_hint = Some(input_name.data.clone())
+                        .filter(|_| core_output_type.references_local(core::LocalIndex(0)));
+                    let core_function_type = core::TermData::FunctionType(
+                        input_name_hint,
+                        Arc::new(core_input_type),
+                        Arc::new(core_output_type),
+                    );
+                    (
+                        core::Term::new(location, core_function_term),
+                        core::Term::generated(core_function_type),
+                    )
+                },
+            );
+
+        let r#type = self.eval(&core_function_type);
+        let core_term = core::Term::new(
+            location,
+            core::TermData::Ann(Arc::new(core_function_term), Arc::new(core_function_type)),
+        );
+
+        (core_term, r#type)
+    }
+
+    /// Check that the type annotation on a function input matches the input
+    /// type expected by the function type the function term is being checked
+    /// against.
+    fn check_input_annotation(&mut self, input_annotation: &Term, expected_type: &Arc<Value>) {
+        if let Some(core_input_annotation) = self.is_type(input_annotation) {
+            let found_type = self.eval(&core_input_annotation);
+            if !self.is_equal(&found_type, expected_type) {
+                let found_type = self.read_back_to_surface(&found_type);
+                let expected_type = self.read_back_to_surface(expected_type);
+                self.report(SurfaceToCoreMessage::MismatchedInputAnnotation {
+                    location: input_annotation.location,
+                    found_type,
+                    expected_type,
+                });
+            }
         }
     }
 
@@ -400,13 +777,36 @@ impl<'me> State<'me> {
     #[debug_ensures(self.local_declarations.size() == old(self.local_declarations.size()))]
     #[debug_ensures(self.local_definitions.size() == old(self.local_definitions.size()))]
     pub fn synth_type(&mut self, term: &Term) -> (core::Term, Arc<Value>) {
+        self.nested(term.location, |state| state.synth_type_unguarded(term))
+            .unwrap_or_else(|| {
+                let error_term = core::Term::new(term.location, core::TermData::Error);
+                (error_term, Arc::new(Value::Error))
+            })
+    }
+
+    /// Synthesize the type of a surface term, returning the elaborated term
+    /// and its type distilled back into [`surface::Term`]s.
+    ///
+    /// This is useful for inspecting the annotations that were inserted
+    /// during elaboration.
+    ///
+    /// [`surface::Term`]: crate::lang::surface::Term
+    pub fn synth_type_to_surface(&mut self, term: &Term) -> (Term, Term) {
+        let (core_term, r#type) = self.synth_type(term);
+        let surface_term = self.core_to_surface(&core_term);
+        let surface_type = self.read_back_to_surface(&r#type);
+
+        (surface_term, surface_type)
+    }
+
+    fn synth_type_unguarded(&mut self, term: &Term) -> (core::Term, Arc<Value>) {
         use std::collections::BTreeMap;
 
         let error_term = || core::Term::new(term.location, core::TermData::Error);
 
         match &term.data {
             TermData::Name(name) => {
-                if let Some((local_index, r#type)) = self.get_local(name.as_ref()) {
+                if let Some((local_index, _, r#type)) = self.get_local(name.as_ref()) {
                     return (
                         core::Term::new(term.location, core::TermData::Local(local_index)),
                         r#type.clone(),
@@ -419,15 +819,33 @@ impl<'me> State<'me> {
                     return (core_term, self.eval(r#type));
                 }
 
+                let similar_name = self.get_similar_local_name(name).cloned();
                 self.report(SurfaceToCoreMessage::UnboundName {
                     location: term.location,
                     name: name.clone(),
+                    similar_name,
+                });
+                (error_term(), Arc::new(Value::Error))
+            }
+
+            TermData::Hole(name) => {
+                self.report(SurfaceToCoreMessage::Hole {
+                    location: term.location,
+                    name: name.clone(),
+                    expected_type: None,
                 });
                 (error_term(), Arc::new(Value::Error))
             }
 
+            TermData::Paren(term) => self.synth_type(term),
+
             TermData::Ann(term, r#type) => {
-                let core_type = match self.is_type(r#type) {
+                let core_type = match self.is_type_or_report(r#type, |location, found_type| {
+                    SurfaceToCoreMessage::AnnotationNotAType {
+                        location,
+                        found_type,
+                    }
+                }) {
                     Some(core_type) => core_type,
                     None => return (error_term(), Arc::new(Value::Error)),
                 };
@@ -447,7 +865,7 @@ impl<'me> State<'me> {
 
                 for (input_names, input_type) in input_type_groups {
                     for input_name in input_names {
-                        let core_input_type = match self.is_type(input_type) {
+                        let core_input_type = match self.is_function_input_type(input_type) {
                             Some(core_input_type) => core_input_type,
                             None => {
                                 self.pop_many_locals(core_inputs.len());
@@ -456,12 +874,12 @@ impl<'me> State<'me> {
                         };
 
                         let core_input_type_value = self.eval(&core_input_type);
-                        self.push_local_param(Some(&input_name.data), core_input_type_value);
+                        self.push_local_param(Some(input_name), core_input_type_value);
                         core_inputs.push((input_name.clone(), core_input_type));
                     }
                 }
 
-                let core_output_type = match self.is_type(output_type) {
+                let core_output_type = match self.is_function_output_type(output_type) {
                     Some(core_output_type) => core_output_type,
                     None => {
                         self.pop_many_locals(core_inputs.len());
@@ -485,14 +903,14 @@ impl<'me> State<'me> {
                 (core_type, Arc::new(Value::TypeType))
             }
             TermData::FunctionArrowType(input_type, output_type) => {
-                let core_input_type = match self.is_type(input_type) {
+                let core_input_type = match self.is_function_input_type(input_type) {
                     Some(core_input_type) => core_input_type,
                     None => return (error_term(), Arc::new(Value::Error)),
                 };
                 let core_input_type_value = self.eval(&core_input_type);
 
                 self.push_local_param(None, core_input_type_value);
-                let (core_term, r#type) = match self.is_type(output_type) {
+                let (core_term, r#type) = match self.is_function_output_type(output_type) {
                     Some(core_output_type) => (
                         core::Term::new(
                             term.location,
@@ -510,16 +928,28 @@ impl<'me> State<'me> {
 
                 (core_term, r#type)
             }
-            TermData::FunctionTerm(_, _) => {
-                self.report(SurfaceToCoreMessage::AmbiguousTerm {
-                    location: term.location,
-                    term: AmbiguousTerm::FunctionTerm,
-                });
-                (error_term(), Arc::new(Value::Error))
+            TermData::FunctionTerm(inputs, output_term) => {
+                let annotated_inputs = (inputs.iter())
+                    .map(|(input_name, input_type)| Some((input_name, input_type.as_ref()?)))
+                    .collect::<Option<Vec<_>>>();
+
+                match annotated_inputs {
+                    Some(annotated_inputs) => {
+                        self.synth_function_term(term.location, &annotated_inputs, output_term)
+                    }
+                    None => {
+                        self.report(SurfaceToCoreMessage::AmbiguousTerm {
+                            location: term.location,
+                            term: AmbiguousTerm::FunctionTerm,
+                        });
+                        (error_term(), Arc::new(Value::Error))
+                    }
+                }
             }
             TermData::FunctionElim(head_term, input_terms) => {
                 let mut head_location = head_term.location;
                 let (mut core_head_term, mut head_type) = self.synth_type(head_term);
+                let input_count = input_terms.len();
                 let mut input_terms = input_terms.iter();
 
                 while let Some(input) = input_terms.next() {
@@ -538,13 +968,29 @@ impl<'me> State<'me> {
                             head_type = output_closure.apply(self.globals, core_input_value);
                         }
                         Value::Error => return (error_term(), Arc::new(Value::Error)),
+                        Value::TypeType => {
+                            let head_term = self.core_to_surface(&core_head_term);
+                            let unexpected_input_terms = std::iter::once(input)
+                                .chain(input_terms)
+                                .map(|arg| arg.location)
+                                .collect();
+                            self.report(SurfaceToCoreMessage::InputsAppliedToType {
+                                head_location,
+                                head_term,
+                                unexpected_input_terms,
+                            });
+                            return (error_term(), Arc::new(Value::Error));
+                        }
                         _ => {
                             let head_type = self.read_back_to_surface(&head_type);
-                            let unexpected_input_terms =
-                                input_terms.map(|arg| arg.location).collect();
+                            let unexpected_input_terms = std::iter::once(input)
+                                .chain(input_terms)
+                                .map(|arg| arg.location)
+                                .collect();
                             self.report(SurfaceToCoreMessage::TooManyInputsInFunctionElim {
                                 head_location,
                                 head_type,
+                                input_count,
                                 unexpected_input_terms,
                             });
                             return (error_term(), Arc::new(Value::Error));
@@ -597,7 +1043,7 @@ impl<'me> State<'me> {
 
                                 labels.push(label.data.clone());
                                 core_types.push(core_type);
-                                self.push_local_param(Some(&param_name.data), core_type_value);
+                                self.push_local_param(Some(param_name), core_type_value);
                                 entry.insert(label.location);
                             }
                             None => {
@@ -732,6 +1178,38 @@ impl<'me> State<'me> {
         core::Term::new(location, term_data)
     }
 
+    /// Warn if a function term defined in a record ignores all of its inputs,
+    /// which is often a sign of a stub or a mistake. Inputs with names that
+    /// start with an underscore are assumed to be ignored on purpose.
+    fn check_ignored_inputs(
+        &self,
+        label: &Located<String>,
+        location: Location,
+        core_term: &core::Term,
+    ) {
+        let mut input_names = Vec::new();
+        let mut output_term = core_term;
+        while let core::TermData::FunctionTerm(input_name, next_output_term) = &output_term.data {
+            input_names.push(input_name);
+            output_term = next_output_term;
+        }
+
+        if input_names
+            .iter()
+            .all(|input_name| input_name.starts_with('_'))
+        {
+            return;
+        }
+
+        let input_count = input_names.len() as u32;
+        if (0..input_count).all(|index| !output_term.references_local(core::LocalIndex(index))) {
+            self.report(SurfaceToCoreMessage::IgnoredFunctionInputs {
+                location,
+                label: label.data.clone(),
+            });
+        }
+    }
+
     fn parse_string(&mut self, location: Location, source: &str) -> core::Term {
         let term_data = literal::State::new(location, source, &self.message_tx)
             .quoted_to_utf8_string()
@@ -741,3 +1219,1230 @@ impl<'me> State<'me> {
         core::Term::new(location, term_data)
     }
 }
+
+/// Parse and elaborate a source file, returning the elaborated term and its
+/// type, or the diagnostics that were produced if any of them were errors.
+pub fn from_source(
+    globals: &core::Globals,
+    file_id: FileId,
+    source: &str,
+) -> Result<(core::Term, Arc<Value>), Vec<Diagnostic<FileId>>> {
+    let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+
+    let surface_term = Term::from_str(file_id, source, &messages_tx);
+    let mut state = State::new(globals, messages_tx);
+    let (core_term, r#type) = state.synth_type(&surface_term);
+
+    into_result(&messages_rx, (core_term, r#type))
+}
+
+/// Parse and elaborate a source file, returning its type as it would be
+/// displayed in diagnostics. This is useful for simple tooling, like showing
+/// the types of terms on hover.
+pub fn synth_type_of_source(
+    globals: &core::Globals,
+    file_id: FileId,
+    source: &str,
+) -> Result<String, Vec<Diagnostic<FileId>>> {
+    let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+
+    let surface_term = Term::from_str(file_id, source, &messages_tx);
+    let mut state = State::new(globals, messages_tx);
+    let (_, r#type) = state.synth_type(&surface_term);
+    let r#type = state.read_back_to_surface(&r#type);
+
+    into_result(&messages_rx, r#type.to_string())
+}
+
+/// Parse and elaborate a term, checking it against a type that is also
+/// parsed from source. The elaborated term is returned, or the diagnostics
+/// that were produced if any of them were errors.
+pub fn check_source(
+    globals: &core::Globals,
+    (file_id, source): (FileId, &str),
+    (type_file_id, type_source): (FileId, &str),
+) -> Result<core::Term, Vec<Diagnostic<FileId>>> {
+    let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+
+    let surface_type = Term::from_str(type_file_id, type_source, &messages_tx);
+    let surface_term = Term::from_str(file_id, source, &messages_tx);
+    let mut state = State::new(globals, messages_tx);
+    let core_term = match state.is_type(&surface_type) {
+        Some(core_type) => {
+            let r#type = state.eval(&core_type);
+            state.check_type(&surface_term, &r#type)
+        }
+        None => core::Term::new(surface_term.location, core::TermData::Error),
+    };
+
+    into_result(&messages_rx, core_term)
+}
+
+/// Convert the messages produced during elaboration into diagnostics,
+/// returning them as an error if any of them were errors.
+pub(crate) fn into_result<T>(
+    messages_rx: &Receiver<Message>,
+    value: T,
+) -> Result<T, Vec<Diagnostic<FileId>>> {
+    let pretty_alloc = pretty::BoxAllocator;
+    let diagnostics = messages_rx
+        .try_iter()
+        .map(|message| message.to_diagnostic(&pretty_alloc))
+        .collect::<Vec<_>>();
+
+    match diagnostics.iter().any(|d| d.severity >= Severity::Error) {
+        true => Err(diagnostics),
+        false => Ok(value),
+    }
+}
+
+/// The number of single character insertions, deletions, and substitutions
+/// needed to turn one string into another.
+fn edit_distance(source: &str, target: &str) -> usize {
+    let target = target.chars().collect::<Vec<_>>();
+    let mut distances = (0..=target.len()).collect::<Vec<_>>();
+
+    for (i, source_char) in source.chars().enumerate() {
+        let mut previous_diagonal = distances[0];
+        distances[0] = i + 1;
+        for (j, target_char) in target.iter().enumerate() {
+            let substitution = previous_diagonal + (source_char != *target_char) as usize;
+            previous_diagonal = distances[j + 1];
+            distances[j + 1] = substitution.min(distances[j] + 1).min(distances[j + 1] + 1);
+        }
+    }
+
+    distances[target.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use codespan_reporting::diagnostic::LabelStyle;
+
+    use super::*;
+
+    fn synth_type(globals: &core::Globals, source: &str) -> (core::Term, Arc<Value>, Vec<Message>) {
+        let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+        let surface_term = Term::from_str(0, source, &messages_tx);
+        let mut state = State::new(globals, messages_tx);
+        let (core_term, r#type) = state.synth_type(&surface_term);
+
+        (core_term, r#type, messages_rx.try_iter().collect())
+    }
+
+    #[test]
+    fn annotation_not_a_type() {
+        let globals = core::Globals::default();
+        let (core_term, _, messages) = synth_type(&globals, "Type : List");
+
+        assert!(matches!(core_term.data, core::TermData::Error));
+        assert!(matches!(
+            messages.as_slice(),
+            [Message::SurfaceToCore(
+                SurfaceToCoreMessage::AnnotationNotAType { .. }
+            )],
+        ));
+    }
+
+    #[test]
+    fn shadowed_name() {
+        let globals = core::Globals::default();
+        let (_, _, messages) = synth_type(&globals, "fun x x => x : Type -> Type -> Type");
+
+        assert!(messages.is_empty());
+    }
+
+    #[test]
+    fn shadowed_name_strict() {
+        let globals = core::Globals::default();
+        let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+        let surface_term = Term::from_str(0, "fun x x => x : Type -> Type -> Type", &messages_tx);
+        let mut state = State::new(&globals, messages_tx);
+        state.set_strict_shadowing(true);
+        state.synth_type(&surface_term);
+        let messages = messages_rx.try_iter().collect::<Vec<_>>();
+
+        assert!(matches!(
+            messages.as_slice(),
+            [Message::SurfaceToCore(SurfaceToCoreMessage::ShadowedName { name, .. })] if name == "x",
+        ));
+    }
+
+    #[test]
+    fn local_type_is_shared() {
+        let globals = core::Globals::default();
+        let (messages_tx, _messages_rx) = crossbeam_channel::unbounded();
+        let mut state = State::new(&globals, messages_tx);
+
+        let r#type = Arc::new(Value::global("String", []));
+        let name = Located::generated("x".to_owned());
+        state.push_local_param(Some(&name), r#type.clone());
+        let (_, found_type) = state.synth_type(&Term::generated(TermData::Name(name.data)));
+        state.pop_local();
+
+        assert!(Arc::ptr_eq(&r#type, &found_type));
+    }
+
+    #[test]
+    fn recursion_limit_exceeded() {
+        let globals = core::Globals::default();
+        let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+
+        // Type -> Type -> ... -> Type
+        let term = (0..64).fold(
+            Term::generated(TermData::Name("Type".to_owned())),
+            |acc, _| {
+                let input = Term::generated(TermData::Name("Type".to_owned()));
+                Term::generated(TermData::FunctionArrowType(Box::new(input), Box::new(acc)))
+            },
+        );
+
+        let mut state = State::new(&globals, messages_tx);
+        state.set_recursion_limit(32);
+        let (core_term, _) = state.synth_type(&term);
+        let messages = messages_rx.try_iter().collect::<Vec<_>>();
+
+        assert!(matches!(core_term.data, core::TermData::FunctionType(..)));
+        assert!(!messages.is_empty());
+        for message in &messages {
+            assert!(matches!(
+                message,
+                Message::SurfaceToCore(SurfaceToCoreMessage::RecursionLimitExceeded {
+                    limit: 32,
+                    ..
+                }),
+            ));
+        }
+    }
+
+    #[test]
+    fn recursion_limit_not_exceeded() {
+        let globals = core::Globals::default();
+        let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+
+        let term = (0..64).fold(
+            Term::generated(TermData::Name("Type".to_owned())),
+            |acc, _| {
+                let input = Term::generated(TermData::Name("Type".to_owned()));
+                Term::generated(TermData::FunctionArrowType(Box::new(input), Box::new(acc)))
+            },
+        );
+
+        let mut state = State::new(&globals, messages_tx);
+        let (_, r#type) = state.synth_type(&term);
+
+        assert!(matches!(r#type.as_ref(), Value::TypeType));
+        assert_eq!(messages_rx.try_iter().count(), 0);
+    }
+
+    #[test]
+    fn normalize_recursion_limit_exceeded() {
+        let globals = core::Globals::default();
+        let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+
+        // Type -> Type -> ... -> Type
+        let term = (0..64).fold(core::Term::generated(core::TermData::TypeType), |acc, _| {
+            let input = Arc::new(core::Term::generated(core::TermData::TypeType));
+            core::Term::generated(core::TermData::FunctionType(None, input, Arc::new(acc)))
+        });
+
+        let mut state = State::new(&globals, messages_tx);
+        state.set_recursion_limit(32);
+        let normal_term = state.normalize(&term);
+
+        assert!(matches!(normal_term.data, core::TermData::Error));
+        assert!(matches!(
+            messages_rx.try_iter().collect::<Vec<_>>().as_slice(),
+            [Message::SurfaceToCore(
+                SurfaceToCoreMessage::RecursionLimitExceeded { limit: 32, .. }
+            )],
+        ));
+    }
+
+    #[test]
+    fn normalize_long_spine_within_recursion_limit() {
+        let globals = core::Globals::default();
+        let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+
+        // List Type Type ... Type
+        let head = core::Term::generated(core::TermData::Global("List".to_owned()));
+        let term = (0..64).fold(head, |head, _| {
+            let input = Arc::new(core::Term::generated(core::TermData::TypeType));
+            core::Term::generated(core::TermData::FunctionElim(Arc::new(head), input))
+        });
+
+        let mut state = State::new(&globals, messages_tx);
+        state.set_recursion_limit(32);
+        let normal_term = state.normalize(&term);
+
+        assert!(matches!(
+            normal_term.data,
+            core::TermData::FunctionElim(_, _)
+        ));
+        assert_eq!(messages_rx.try_iter().count(), 0);
+    }
+
+    #[test]
+    fn named_hole_goal() {
+        let globals = core::Globals::default();
+        let (core_term, _, messages) = synth_type(&globals, "?foo : Type");
+
+        assert!(matches!(core_term.data, core::TermData::Ann(..)));
+        assert_eq!(messages.len(), 1);
+        match &messages[0] {
+            Message::SurfaceToCore(SurfaceToCoreMessage::Hole {
+                name: Some(name),
+                expected_type: Some(_),
+                ..
+            }) => assert_eq!(name, "foo"),
+            message => panic!("unexpected message: {:?}", message),
+        }
+
+        let pretty_alloc = pretty::BoxAllocator;
+        let diagnostic = messages[0].to_diagnostic(&pretty_alloc);
+        assert!(diagnostic.message.contains("?foo"));
+        assert_eq!(diagnostic.labels.len(), 1);
+        assert_eq!(diagnostic.labels[0].message, "goal `foo` : `Type`");
+    }
+
+    #[test]
+    fn ambiguous_hole() {
+        let globals = core::Globals::default();
+        let (core_term, r#type, messages) = synth_type(&globals, "?");
+
+        assert!(matches!(core_term.data, core::TermData::Error));
+        assert!(matches!(r#type.as_ref(), Value::Error));
+        assert!(matches!(
+            messages.as_slice(),
+            [Message::SurfaceToCore(SurfaceToCoreMessage::Hole {
+                name: None,
+                expected_type: None,
+                ..
+            })],
+        ));
+    }
+
+    #[test]
+    fn polymorphic_entry_used_at_many_types() {
+        let globals = core::Globals::default();
+        let (_, _, messages) = synth_type(
+            &globals,
+            r"
+                record {
+                    id = fun A a => a,
+                    test = record {
+                        at-type = id Type Bool,
+                        at-function = id (Type -> Type) (fun A => A),
+                    },
+                } : Record {
+                    id : Fun (A : Type) -> A -> A,
+                    test : Record {
+                        at-type : Type,
+                        at-function : Type -> Type,
+                    },
+                }
+            ",
+        );
+
+        assert!(messages.is_empty(), "unexpected messages: {:?}", messages);
+    }
+
+    #[test]
+    fn from_source_ok() {
+        let globals = core::Globals::default();
+        let (core_term, r#type) = from_source(&globals, 0, "Bool : Type").unwrap();
+
+        assert!(matches!(core_term.data, core::TermData::Ann(..)));
+        assert!(matches!(r#type.force(&globals), Value::TypeType));
+    }
+
+    #[test]
+    fn from_source_error() {
+        let globals = core::Globals::default();
+        let diagnostics = from_source(&globals, 0, "Bool : Type -> Type").unwrap_err();
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert_eq!(diagnostics[0].labels.len(), 1);
+        assert_eq!(diagnostics[0].labels[0].file_id, 0);
+        assert_eq!(diagnostics[0].labels[0].range, 0..4);
+    }
+
+    #[test]
+    fn synth_type_of_source_ok() {
+        let globals = core::Globals::default();
+        let r#type =
+            synth_type_of_source(&globals, 0, "fun (a : Type) => fun (x : a) => x").unwrap();
+
+        assert_eq!(r#type, "Fun (a : Type) -> Fun (t : a) -> a");
+    }
+
+    #[test]
+    fn synth_type_of_source_error() {
+        let globals = core::Globals::default();
+        let diagnostics = synth_type_of_source(&globals, 0, "fun a => a").unwrap_err();
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+    }
+
+    #[test]
+    fn check_source_ok() {
+        let globals = core::Globals::default();
+        let core_term = check_source(
+            &globals,
+            (0, "fun A a => a"),
+            (1, "Fun (A : Type) -> A -> A"),
+        )
+        .unwrap();
+
+        assert!(matches!(core_term.data, core::TermData::FunctionTerm(..)));
+    }
+
+    #[test]
+    fn check_source_error() {
+        let globals = core::Globals::default();
+        let diagnostics = check_source(&globals, (0, "fun A a => a"), (1, "Type")).unwrap_err();
+
+        assert!(!diagnostics.is_empty());
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert_eq!(diagnostics[0].labels[0].file_id, 0);
+    }
+
+    #[test]
+    fn check_source_type_error() {
+        let globals = core::Globals::default();
+        let diagnostics = check_source(&globals, (0, "Type"), (1, "true")).unwrap_err();
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].labels[0].file_id, 1);
+    }
+
+    #[test]
+    fn check_function_redex() {
+        let globals = core::Globals::default();
+        let (core_term, r#type, messages) = synth_type(&globals, "(fun x => x) Type : Type");
+
+        assert!(messages.is_empty(), "unexpected messages: {:?}", messages);
+        assert!(matches!(r#type.force(&globals), Value::TypeType));
+        match &core_term.data {
+            core::TermData::Ann(term, _) => {
+                assert!(matches!(&term.data, core::TermData::Global(name) if name == "Type"));
+            }
+            _ => panic!("expected an annotated term, found {:?}", core_term),
+        }
+
+        // The elaborated term should be accepted by the core type checker
+        let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+        core::typing::State::new(&globals, messages_tx).synth_type(&core_term);
+        assert_eq!(messages_rx.try_iter().count(), 0);
+    }
+
+    #[test]
+    fn check_dependent_function_redex() {
+        let globals = core::Globals::default();
+        let (core_term, _, messages) = synth_type(&globals, "(fun A a => a) Type Bool : Type");
+
+        assert!(messages.is_empty(), "unexpected messages: {:?}", messages);
+
+        let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+        core::typing::State::new(&globals, messages_tx).synth_type(&core_term);
+        assert_eq!(messages_rx.try_iter().count(), 0);
+    }
+
+    #[test]
+    fn check_function_redex_with_input_values() {
+        let globals = core::Globals::default();
+        let (core_term, _, messages) =
+            synth_type(&globals, "(fun A x => (x : A)) Bool true : Bool");
+
+        assert!(messages.is_empty(), "unexpected messages: {:?}", messages);
+
+        let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+        core::typing::State::new(&globals, messages_tx).synth_type(&core_term);
+        assert_eq!(messages_rx.try_iter().count(), 0);
+    }
+
+    #[test]
+    fn check_function_redex_mismatch() {
+        let globals = core::Globals::default();
+        let (_, _, messages) = synth_type(&globals, "(fun x => x) true : Type");
+
+        assert!(matches!(
+            messages.as_slice(),
+            [Message::SurfaceToCore(
+                SurfaceToCoreMessage::MismatchedTypes { .. }
+            )],
+        ));
+    }
+
+    #[test]
+    fn record_filled_input_annotations() {
+        let globals = core::Globals::default();
+        let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+        let surface_term = Term::from_str(0, "fun x => x", &messages_tx);
+        let surface_type = Term::from_str(0, "Type -> Type", &messages_tx);
+
+        let mut state = State::new(&globals, messages_tx);
+        state.set_record_edits(true);
+        let core_type = state.is_type(&surface_type).unwrap();
+        let r#type = state.eval(&core_type);
+        state.check_type(&surface_term, &r#type);
+        let edits = state.take_edits();
+
+        assert_eq!(messages_rx.try_iter().count(), 0);
+        assert_eq!(
+            edits.iter().map(Edit::to_string).collect::<Vec<_>>(),
+            ["filled annotation of input `x` with `Type`"],
+        );
+        assert!(state.take_edits().is_empty());
+    }
+
+    #[test]
+    fn function_input_not_a_type() {
+        let globals = core::Globals::default();
+
+        for source in &["Fun (x : true) -> Type", "true -> Type"] {
+            let (core_term, _, messages) = synth_type(&globals, source);
+
+            assert!(matches!(core_term.data, core::TermData::Error));
+            assert!(
+                matches!(
+                    messages.as_slice(),
+                    [Message::SurfaceToCore(
+                        SurfaceToCoreMessage::FunctionInputNotAType { .. }
+                    )],
+                ),
+                "unexpected messages for `{}`: {:?}",
+                source,
+                messages,
+            );
+        }
+    }
+
+    #[test]
+    fn function_output_not_a_type() {
+        let globals = core::Globals::default();
+
+        for source in &["Fun (x : Type) -> true", "Type -> true"] {
+            let (core_term, _, messages) = synth_type(&globals, source);
+
+            assert!(matches!(core_term.data, core::TermData::Error));
+            assert!(
+                matches!(
+                    messages.as_slice(),
+                    [Message::SurfaceToCore(
+                        SurfaceToCoreMessage::FunctionOutputNotAType { .. }
+                    )],
+                ),
+                "unexpected messages for `{}`: {:?}",
+                source,
+                messages,
+            );
+        }
+    }
+
+    #[test]
+    fn too_many_inputs_in_function_elim() {
+        let globals = core::Globals::default();
+        let (_, _, messages) = synth_type(
+            &globals,
+            "(fun A a => a : Fun (A : Type) -> A -> A) Bool true false true",
+        );
+
+        match messages.as_slice() {
+            [Message::SurfaceToCore(SurfaceToCoreMessage::TooManyInputsInFunctionElim {
+                input_count,
+                unexpected_input_terms,
+                ..
+            })] => {
+                assert_eq!(*input_count, 4);
+                assert_eq!(unexpected_input_terms.len(), 2);
+            }
+            messages => panic!("unexpected messages: {:?}", messages),
+        }
+
+        let pretty_alloc = pretty::BoxAllocator;
+        let diagnostic = messages[0].to_diagnostic(&pretty_alloc);
+        assert_eq!(diagnostic.notes, ["expected at most 2 inputs, found 4"]);
+    }
+
+    #[test]
+    fn duplicate_record_term_labels() {
+        let globals = core::Globals::default();
+        let (_, _, messages) = synth_type(
+            &globals,
+            "record { id = Type, id = Type } : Record { id : Type }",
+        );
+
+        match messages.as_slice() {
+            [Message::SurfaceToCore(SurfaceToCoreMessage::InvalidRecordTerm {
+                duplicate_labels,
+                missing_labels,
+                unexpected_labels,
+                ..
+            })] => {
+                let duplicate_names = duplicate_labels.iter().map(|(name, _, _)| name.as_str());
+                assert_eq!(duplicate_names.collect::<Vec<_>>(), ["id"]);
+                assert!(missing_labels.is_empty());
+                assert!(unexpected_labels.is_empty());
+            }
+            messages => panic!("unexpected messages: {:?}", messages),
+        }
+    }
+
+    fn to_pretty(term: &Term) -> String {
+        let pretty_alloc = pretty::BoxAllocator;
+        let doc = crate::pass::surface_to_pretty::from_term(&pretty_alloc, term).1;
+        let output = doc.pretty(usize::MAX).to_string();
+        output
+    }
+
+    #[test]
+    fn synth_type_to_surface_shows_annotations() {
+        let globals = core::Globals::default();
+        let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+        let surface_term =
+            Term::from_str(0, "fun A a => a : Fun (A : Type) -> A -> A", &messages_tx);
+        let expected_term = Term::from_str(
+            0,
+            "fun A a => a : Fun (A : Type) -> Fun (t : A) -> A",
+            &messages_tx,
+        );
+        let expected_type = Term::from_str(0, "Fun (A : Type) -> Fun (t : A) -> A", &messages_tx);
+
+        let mut state = State::new(&globals, messages_tx);
+        let (elaborated_term, r#type) = state.synth_type_to_surface(&surface_term);
+
+        assert_eq!(messages_rx.try_iter().count(), 0);
+        assert_eq!(to_pretty(&elaborated_term), to_pretty(&expected_term));
+        assert_eq!(to_pretty(&r#type), to_pretty(&expected_type));
+    }
+
+    #[test]
+    fn ignored_function_inputs() {
+        let globals = core::Globals::default();
+
+        let (_, _, messages) = synth_type(
+            &globals,
+            "record { f = fun x => Type } : Record { f : Type -> Type }",
+        );
+        match messages.as_slice() {
+            [Message::SurfaceToCore(SurfaceToCoreMessage::IgnoredFunctionInputs {
+                label, ..
+            })] => assert_eq!(label, "f"),
+            messages => panic!("unexpected messages: {:?}", messages),
+        }
+
+        let sources = [
+            "record { f = fun x => x } : Record { f : Type -> Type }",
+            "record { f = fun _x => Type } : Record { f : Type -> Type }",
+            "record { f = fun _ => Type } : Record { f : Type -> Type }",
+            "record { f = Type } : Record { f : Type }",
+        ];
+        for source in &sources {
+            let (_, _, messages) = synth_type(&globals, source);
+            assert!(
+                messages.is_empty(),
+                "unexpected messages in `{}`: {:?}",
+                source,
+                messages
+            );
+        }
+    }
+
+    #[test]
+    fn check_against_stuck_type() {
+        let globals = core::Globals::default();
+        let sources = [
+            "fun F A a => a : Fun (F : Type -> Type) (A : Type) -> F A -> F A",
+            "fun F A f a => f a : Fun (F : Type -> Type) (A : Type) -> (A -> F A) -> A -> F A",
+            "fun F A xs => xs : Fun (F : Type -> Type) (A : Type) -> List (F A) -> List (F A)",
+        ];
+
+        for source in &sources {
+            let (_, _, messages) = synth_type(&globals, source);
+            assert!(
+                messages.is_empty(),
+                "unexpected messages in `{}`: {:?}",
+                source,
+                messages
+            );
+        }
+
+        let (_, _, messages) = synth_type(
+            &globals,
+            "fun F A B a => a : Fun (F : Type -> Type) (A B : Type) -> F A -> F B",
+        );
+        match messages.as_slice() {
+            [Message::SurfaceToCore(SurfaceToCoreMessage::MismatchedTypes { .. })] => {}
+            messages => panic!("unexpected messages: {:?}", messages),
+        }
+    }
+
+    #[test]
+    fn hole_in_input_position() {
+        let globals = core::Globals::default();
+        let sources = [
+            "List ?",
+            "Array 3 ?",
+            "(fun A a => a : Fun (A : Type) -> A -> A) Type ?",
+        ];
+
+        for source in &sources {
+            let (_, _, messages) = synth_type(&globals, source);
+            match messages.as_slice() {
+                [Message::SurfaceToCore(SurfaceToCoreMessage::Hole {
+                    expected_type: Some(expected_type),
+                    ..
+                })] => assert_eq!(to_pretty(expected_type), "Type", "in `{}`", source),
+                messages => panic!("unexpected messages in `{}`: {:?}", source, messages),
+            }
+        }
+    }
+
+    #[test]
+    fn ambiguous_number_literal_lists_types() {
+        let globals = core::Globals::default();
+        let (_, _, messages) = synth_type(&globals, "1");
+
+        match messages.as_slice() {
+            [message @ Message::SurfaceToCore(SurfaceToCoreMessage::AmbiguousTerm {
+                term: AmbiguousTerm::NumberLiteral,
+                ..
+            })] => {
+                let pretty_alloc = pretty::BoxAllocator;
+                let diagnostic = message.to_diagnostic(&pretty_alloc);
+                assert_eq!(diagnostic.notes.len(), 1);
+                for name in &["U8", "S64", "F32"] {
+                    assert!(diagnostic.notes[0].contains(&format!("`{}`", name)));
+                }
+            }
+            messages => panic!("unexpected messages: {:?}", messages),
+        }
+    }
+
+    #[test]
+    fn inputs_applied_to_type() {
+        let globals = core::Globals::default();
+
+        let (_, _, messages) = synth_type(&globals, "Type Type");
+        match messages.as_slice() {
+            [message @ Message::SurfaceToCore(SurfaceToCoreMessage::InputsAppliedToType {
+                ..
+            })] => {
+                let pretty_alloc = pretty::BoxAllocator;
+                let diagnostic = message.to_diagnostic(&pretty_alloc);
+                assert_eq!(diagnostic.message, "`Type` is not a function");
+
+                let ranges = diagnostic.labels.iter().map(|label| label.range.clone());
+                assert_eq!(ranges.collect::<Vec<_>>(), [0..4, 5..9]);
+            }
+            messages => panic!("unexpected messages: {:?}", messages),
+        }
+
+        let (_, _, messages) = synth_type(&globals, "List Type Type");
+        match messages.as_slice() {
+            [Message::SurfaceToCore(SurfaceToCoreMessage::InputsAppliedToType {
+                head_term,
+                unexpected_input_terms,
+                ..
+            })] => {
+                assert_eq!(to_pretty(head_term), "List Type");
+                assert_eq!(unexpected_input_terms.len(), 1);
+            }
+            messages => panic!("unexpected messages: {:?}", messages),
+        }
+    }
+
+    #[test]
+    fn annotated_function_inputs() {
+        let globals = core::Globals::default();
+
+        let sources = [
+            "fun (x : Type) => x : Type -> Type",
+            "fun (A : Type) (a : A) => a : Fun (A : Type) -> A -> A",
+            "fun (A B : Type) a => a : Fun (A B : Type) -> A -> A",
+        ];
+        for source in &sources {
+            let (_, _, messages) = synth_type(&globals, source);
+            assert!(
+                messages.is_empty(),
+                "unexpected messages in `{}`: {:?}",
+                source,
+                messages
+            );
+        }
+
+        let (_, _, messages) = synth_type(&globals, "fun (x : Type -> Type) => x : Type -> Type");
+        match messages.as_slice() {
+            [Message::SurfaceToCore(SurfaceToCoreMessage::MismatchedInputAnnotation {
+                location,
+                found_type,
+                expected_type,
+            })] => {
+                match location {
+                    Location::FileRange(_, range) => {
+                        assert_eq!(std::ops::Range::from(*range), 9..21)
+                    }
+                    Location::Generated => panic!("expected a file range"),
+                }
+                assert_eq!(to_pretty(found_type), "Fun (t : Type) -> Type");
+                assert_eq!(to_pretty(expected_type), "Type");
+            }
+            messages => panic!("unexpected messages: {:?}", messages),
+        }
+    }
+
+    #[test]
+    fn dependent_function_input_annotations() {
+        let globals = core::Globals::default();
+
+        let sources = [
+            "fun (a : Type) (x : a) => x : Fun (a : Type) -> a -> a",
+            "fun (a : Type) (b : Type) (f : a -> b) => f : Fun (a b : Type) -> (a -> b) -> a -> b",
+            "fun (a b : Type) (f : a -> b) x => f x : Fun (a b : Type) -> (a -> b) -> a -> b",
+        ];
+        for source in &sources {
+            let (_, _, messages) = synth_type(&globals, source);
+            assert!(
+                messages.is_empty(),
+                "unexpected messages in `{}`: {:?}",
+                source,
+                messages
+            );
+        }
+
+        let (_, _, messages) = synth_type(
+            &globals,
+            "fun (a b : Type) (f : b -> a) => f : Fun (a b : Type) -> (a -> b) -> a -> b",
+        );
+        match messages.as_slice() {
+            [Message::SurfaceToCore(SurfaceToCoreMessage::MismatchedInputAnnotation {
+                found_type,
+                expected_type,
+                ..
+            })] => {
+                assert_eq!(to_pretty(found_type), "Fun (t : b) -> a");
+                assert_eq!(to_pretty(expected_type), "Fun (t : a) -> b");
+            }
+            messages => panic!("unexpected messages: {:?}", messages),
+        }
+    }
+
+    #[test]
+    fn synth_annotated_function_terms() {
+        let globals = core::Globals::default();
+
+        let cases = [
+            ("fun (x : Type) => x", "Fun (t : Type) -> Type"),
+            (
+                "fun (a : Type) => fun (x : a) => x",
+                "Fun (a : Type) -> Fun (t : a) -> a",
+            ),
+            (
+                "fun (a : Type) (x : a) => x",
+                "Fun (a : Type) -> Fun (t : a) -> a",
+            ),
+            (
+                "fun (a b : Type) (f : a -> b) (x : a) => f x",
+                "Fun (a : Type) -> Fun (b : Type) -> Fun (t : Fun (t : a) -> b) -> Fun (t-1 : a) -> b",
+            ),
+            ("fun (a : Type) => List a", "Fun (t : Type) -> Type"),
+        ];
+
+        for (source, expected_type) in &cases {
+            let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+            let surface_term = Term::from_str(0, source, &messages_tx);
+            let mut state = State::new(&globals, messages_tx);
+            let (_, r#type) = state.synth_type_to_surface(&surface_term);
+
+            let messages = messages_rx.try_iter().collect::<Vec<_>>();
+            assert!(
+                messages.is_empty(),
+                "unexpected messages in `{}`: {:?}",
+                source,
+                messages
+            );
+            assert_eq!(to_pretty(&r#type), *expected_type, "in `{}`", source);
+        }
+
+        let (_, _, messages) = synth_type(&globals, "fun (a : Type) x => x");
+        assert!(matches!(
+            messages.as_slice(),
+            [Message::SurfaceToCore(
+                SurfaceToCoreMessage::AmbiguousTerm {
+                    term: AmbiguousTerm::FunctionTerm,
+                    ..
+                }
+            )],
+        ));
+    }
+
+    #[test]
+    fn mixed_function_type_chains() {
+        let globals = core::Globals::default();
+
+        let sources = [
+            "fun a x b => x : Fun (a : Type) -> a -> Fun (b : Type) -> a",
+            "fun a x b y => y : Fun (a : Type) -> a -> Fun (b : Type) -> b -> b",
+            "fun a x b y => x : Fun (a : Type) -> a -> Fun (b : Type) -> b -> a",
+            "fun a f x => f x : Fun (a : Type) -> (a -> a) -> a -> a",
+        ];
+        for source in &sources {
+            let (_, _, messages) = synth_type(&globals, source);
+            assert!(
+                messages.is_empty(),
+                "unexpected messages in `{}`: {:?}",
+                source,
+                messages
+            );
+        }
+
+        let (_, _, messages) = synth_type(
+            &globals,
+            "fun a x b y => x : Fun (a : Type) -> a -> Fun (b : Type) -> b -> b",
+        );
+        match messages.as_slice() {
+            [Message::SurfaceToCore(SurfaceToCoreMessage::MismatchedTypes {
+                found_type,
+                expected_type: ExpectedType::Type(expected_type),
+                ..
+            })] => {
+                assert_eq!(to_pretty(found_type), "a");
+                assert_eq!(to_pretty(expected_type), "b");
+            }
+            messages => panic!("unexpected messages: {:?}", messages),
+        }
+    }
+
+    #[test]
+    fn dependent_function_types() {
+        let globals = core::Globals::default();
+        let sources = [
+            "Fun (a : Type) -> a",
+            "Fun (f : Type -> Type) -> f Type",
+            "Fun (f : Type -> Type) (a : Type) -> f a -> f (f a)",
+            "Fun (a : Type) (b : a -> Type) -> Fun (x : a) -> b x",
+        ];
+
+        for source in &sources {
+            let (core_term, r#type, messages) = synth_type(&globals, source);
+            assert!(
+                messages.is_empty(),
+                "unexpected messages in `{}`: {:?}",
+                source,
+                messages
+            );
+            assert!(matches!(r#type.force(&globals), Value::TypeType));
+
+            let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+            let core_type = core::typing::State::new(&globals, messages_tx).synth_type(&core_term);
+            let messages = messages_rx.try_iter().collect::<Vec<_>>();
+            assert!(
+                messages.is_empty(),
+                "unexpected core typing messages in `{}`: {:?}",
+                source,
+                messages
+            );
+            assert!(matches!(core_type.force(&globals), Value::TypeType));
+        }
+    }
+
+    #[test]
+    fn edit_distances() {
+        assert_eq!(edit_distance("length", "length"), 0);
+        assert_eq!(edit_distance("lenght", "length"), 2);
+        assert_eq!(edit_distance("lengths", "length"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn unbound_name_suggests_similar_local() {
+        let globals = core::Globals::default();
+        let (_, _, messages) = synth_type(&globals, "fun length => lengt : Type -> Type");
+
+        match messages.as_slice() {
+            [message @ Message::SurfaceToCore(SurfaceToCoreMessage::UnboundName {
+                similar_name: Some(similar_name),
+                ..
+            })] => {
+                assert_eq!(similar_name.data, "length");
+
+                let pretty_alloc = pretty::BoxAllocator;
+                let diagnostic = message.to_diagnostic(&pretty_alloc);
+                let labels = (diagnostic.labels.iter())
+                    .map(|label| (label.style, label.range.clone()))
+                    .collect::<Vec<_>>();
+                assert_eq!(
+                    labels,
+                    [
+                        (LabelStyle::Primary, 14..19),
+                        (LabelStyle::Secondary, 4..10)
+                    ],
+                );
+                assert_eq!(diagnostic.notes, ["help: did you mean `length`?"]);
+            }
+            messages => panic!("unexpected messages: {:?}", messages),
+        }
+
+        let (_, _, messages) = synth_type(&globals, "fun length => width : Type -> Type");
+        assert!(matches!(
+            messages.as_slice(),
+            [Message::SurfaceToCore(SurfaceToCoreMessage::UnboundName {
+                similar_name: None,
+                ..
+            })],
+        ));
+    }
+
+    #[test]
+    fn check_against_type_alias() {
+        use fxhash::FxHashMap;
+
+        let mut entries = (core::Globals::default().entries())
+            .map(|(name, entry)| (name.clone(), entry.clone()))
+            .collect::<FxHashMap<_, _>>();
+        entries.insert(
+            "StringAlias".to_owned(),
+            (
+                Arc::new(core::Term::generated(core::TermData::TypeType)),
+                Some(Arc::new(core::Term::generated(core::TermData::Global(
+                    "String".to_owned(),
+                )))),
+            ),
+        );
+        let mut globals = core::Globals::new(entries);
+
+        let sources = [
+            r#""x" : StringAlias"#,
+            r#"record { S = String, x = "x" } : Record { S : Type, x : S }"#,
+        ];
+        for source in &sources {
+            let (_, _, messages) = synth_type(&globals, source);
+            assert!(
+                messages.is_empty(),
+                "unexpected messages in `{}`: {:?}",
+                source,
+                messages
+            );
+        }
+
+        globals.set_opaque("StringAlias", true);
+        let (_, _, messages) = synth_type(&globals, r#""x" : StringAlias"#);
+        match messages.as_slice() {
+            [Message::SurfaceToCore(SurfaceToCoreMessage::NoLiteralConversion { .. })] => {}
+            messages => panic!("unexpected messages: {:?}", messages),
+        }
+    }
+
+    #[test]
+    fn eliminate_never() {
+        let globals = core::Globals::default();
+        let sources = [
+            "absurd : Fun (A : Type) -> Never -> A",
+            "fun never => absurd String never : Never -> String",
+            "fun A never => absurd A never : Fun (A : Type) -> Never -> A",
+            "fun never => absurd (List Bool) never : Never -> List Bool",
+        ];
+
+        for source in &sources {
+            let (core_term, _, messages) = synth_type(&globals, source);
+            assert!(
+                messages.is_empty(),
+                "unexpected messages in `{}`: {:?}",
+                source,
+                messages
+            );
+
+            let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+            core::typing::State::new(&globals, messages_tx).synth_type(&core_term);
+            let messages = messages_rx.try_iter().collect::<Vec<_>>();
+            assert!(
+                messages.is_empty(),
+                "unexpected core typing messages in `{}`: {:?}",
+                source,
+                messages
+            );
+        }
+
+        let (_, _, messages) = synth_type(&globals, "absurd String true : String");
+        match messages.as_slice() {
+            [Message::SurfaceToCore(SurfaceToCoreMessage::MismatchedTypes { .. })] => {}
+            messages => panic!("unexpected messages: {:?}", messages),
+        }
+    }
+
+    #[test]
+    fn builtin_types_in_type_positions() {
+        let globals = core::Globals::default();
+        let cases = [
+            ("F64 -> F64", "Type"),
+            ("Fun (x : U8) -> S32", "Type"),
+            ("fun (x : U8) => x", "Fun (t : U8) -> U8"),
+            (
+                "fun (f : F64 -> F64) (x : F64) => f x",
+                "Fun (t : Fun (t : F64) -> F64) -> Fun (t-1 : F64) -> F64",
+            ),
+            ("List (Array 3 Char)", "Type"),
+            ("String : Type", "Type"),
+        ];
+
+        for (source, expected_type) in &cases {
+            let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+            let surface_term = Term::from_str(0, source, &messages_tx);
+            let mut state = State::new(&globals, messages_tx);
+            let (_, r#type) = state.synth_type_to_surface(&surface_term);
+
+            let messages = messages_rx.try_iter().collect::<Vec<_>>();
+            assert!(
+                messages.is_empty(),
+                "unexpected messages in `{}`: {:?}",
+                source,
+                messages
+            );
+            assert_eq!(to_pretty(&r#type), *expected_type, "in `{}`", source);
+        }
+    }
+
+    #[test]
+    fn polymorphic_definitions_at_multiple_types() {
+        let globals = core::Globals::default();
+
+        let (_, _, messages) = synth_type(
+            &globals,
+            "record {
+                id = fun A a => a,
+                F = id (Type -> Type) List,
+                x = id Type (F (id Type Bool)),
+            } : Record {
+                id : Fun (A : Type) -> A -> A,
+                F : Type -> Type,
+                x : Type,
+            }",
+        );
+        assert!(messages.is_empty(), "unexpected messages: {:?}", messages);
+
+        let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+        let surface_term = Term::from_str(
+            0,
+            "fun (id : Fun (A : Type) -> A -> A) => id Type (id (Type -> Type) List Bool)",
+            &messages_tx,
+        );
+        let mut state = State::new(&globals, messages_tx);
+        let (_, r#type) = state.synth_type_to_surface(&surface_term);
+
+        let messages = messages_rx.try_iter().collect::<Vec<_>>();
+        assert!(messages.is_empty(), "unexpected messages: {:?}", messages);
+        assert_eq!(
+            to_pretty(&r#type),
+            "Fun (t : Fun (A : Type) -> Fun (t : A) -> A) -> Type",
+        );
+    }
+
+    #[test]
+    fn check_against_eta_expanded_type() {
+        let globals = core::Globals::default();
+        let sources = [
+            "fun G F x => x : Fun (G : (Type -> Type) -> Type) (F : Type -> Type) -> G F -> G F",
+            "fun G F x => x : Fun (G : (Type -> Type) -> Type) (F : Type -> Type) -> G F -> G (fun A => F A)",
+            "fun G F x => x : Fun (G : (Type -> Type) -> Type) (F : Type -> Type) -> G (fun A => F A) -> G F",
+        ];
+
+        for source in &sources {
+            let (_, _, messages) = synth_type(&globals, source);
+            assert!(
+                messages.is_empty(),
+                "unexpected messages in `{}`: {:?}",
+                source,
+                messages
+            );
+        }
+
+        let (_, _, messages) = synth_type(
+            &globals,
+            "fun G F x => x : Fun (G : (Type -> Type) -> Type) (F : Type -> Type) -> G F -> G (fun A => F Type)",
+        );
+        match messages.as_slice() {
+            [Message::SurfaceToCore(SurfaceToCoreMessage::MismatchedTypes { .. })] => {}
+            messages => panic!("unexpected messages: {:?}", messages),
+        }
+    }
+
+    #[test]
+    fn check_boolean_and_unit_terms() {
+        let globals = core::Globals::default();
+        let sources = [
+            "true : Bool",
+            "false : Bool",
+            "[true, false] : List Bool",
+            "record {} : Record {}",
+        ];
+
+        for source in &sources {
+            let (_, _, messages) = synth_type(&globals, source);
+            assert!(
+                messages.is_empty(),
+                "unexpected messages in `{}`: {:?}",
+                source,
+                messages
+            );
+        }
+
+        let (_, _, messages) = synth_type(&globals, "true : Record {}");
+        match messages.as_slice() {
+            [Message::SurfaceToCore(SurfaceToCoreMessage::MismatchedTypes { .. })] => {}
+            messages => panic!("unexpected messages: {:?}", messages),
+        }
+    }
+
+    #[test]
+    fn mismatched_input_in_spine() {
+        let globals = core::Globals::default();
+        let (_, _, messages) = synth_type(
+            &globals,
+            "fun (f : Type -> Bool -> Type -> Type) => f Type true false",
+        );
+
+        match messages.as_slice() {
+            [Message::SurfaceToCore(SurfaceToCoreMessage::MismatchedTypes {
+                location,
+                found_type,
+                expected_type: ExpectedType::Type(expected_type),
+            })] => {
+                match location {
+                    Location::FileRange(_, range) => {
+                        assert_eq!(std::ops::Range::from(*range), 54..59)
+                    }
+                    Location::Generated => panic!("expected a file range"),
+                }
+                assert_eq!(to_pretty(found_type), "Bool");
+                assert_eq!(to_pretty(expected_type), "Type");
+            }
+            messages => panic!("unexpected messages: {:?}", messages),
+        }
+    }
+
+    #[test]
+    fn partial_application_inputs() {
+        let globals = core::Globals::default();
+        let (_, r#type, messages) = synth_type(
+            &globals,
+            "(fun A a b => A : Fun (A : Type) -> A -> Bool -> Type) String",
+        );
+        assert!(messages.is_empty(), "unexpected messages: {:?}", messages);
+
+        let (messages_tx, _) = crossbeam_channel::unbounded();
+        let mut state = State::new(&globals, messages_tx);
+        let inputs = semantics::function_type_inputs(&globals, state.size(), &r#type);
+        let inputs = (inputs.iter())
+            .map(|(name, r#type)| (name.clone(), to_pretty(&state.read_back_to_surface(r#type))))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            inputs,
+            [(None, "String".to_owned()), (None, "Bool".to_owned())],
+        );
+    }
+}
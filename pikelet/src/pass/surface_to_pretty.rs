@@ -16,15 +16,50 @@ pub enum Prec {
     Atomic,
 }
 
+/// How [parenthesized terms](TermData::Paren) should be printed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Parens {
+    /// Only print the parentheses required by the precedence of the terms.
+    Minimal,
+    /// Print the parentheses that were written in the source code, leaving
+    /// the grouping chosen by the user intact.
+    Preserve,
+}
+
 pub fn from_term<'a, D>(alloc: &'a D, term: &'a Term) -> DocBuilder<'a, D>
 where
     D: DocAllocator<'a>,
     D::Doc: Clone,
 {
-    from_term_prec(alloc, term, Prec::Term)
+    from_term_with_parens(alloc, Parens::Minimal, term)
 }
 
 pub fn from_term_prec<'a, D>(alloc: &'a D, term: &'a Term, prec: Prec) -> DocBuilder<'a, D>
+where
+    D: DocAllocator<'a>,
+    D::Doc: Clone,
+{
+    from_term_prec_with_parens(alloc, Parens::Minimal, term, prec)
+}
+
+pub fn from_term_with_parens<'a, D>(
+    alloc: &'a D,
+    parens: Parens,
+    term: &'a Term,
+) -> DocBuilder<'a, D>
+where
+    D: DocAllocator<'a>,
+    D::Doc: Clone,
+{
+    from_term_prec_with_parens(alloc, parens, term, Prec::Term)
+}
+
+pub fn from_term_prec_with_parens<'a, D>(
+    alloc: &'a D,
+    parens: Parens,
+    term: &'a Term,
+    prec: Prec,
+) -> DocBuilder<'a, D>
 where
     D: DocAllocator<'a>,
     D::Doc: Clone,
@@ -32,16 +67,29 @@ where
     match &term.data {
         TermData::Name(name) => alloc.text(name),
 
+        TermData::Paren(term) => match parens {
+            Parens::Minimal => from_term_prec_with_parens(alloc, parens, term, prec),
+            Parens::Preserve => (alloc.nil())
+                .append("(")
+                .append(from_term_prec_with_parens(alloc, parens, term, Prec::Term))
+                .append(")"),
+        },
+
         TermData::Ann(term, r#type) => paren(
             alloc,
             prec > Prec::Term,
             (alloc.nil())
-                .append(from_term_prec(alloc, term, Prec::Expr))
+                .append(from_term_prec_with_parens(alloc, parens, term, Prec::Expr))
                 .append(alloc.space())
                 .append(":")
                 .append(
                     (alloc.space())
-                        .append(from_term_prec(alloc, r#type, Prec::Term))
+                        .append(from_term_prec_with_parens(
+                            alloc,
+                            parens,
+                            r#type,
+                            Prec::Term,
+                        ))
                         .group()
                         .nest(4),
                 ),
@@ -64,7 +112,12 @@ where
                             .append(alloc.space())
                             .append(":")
                             .append(alloc.space())
-                            .append(from_term_prec(alloc, input_type, Prec::Term))
+                            .append(from_term_prec_with_parens(
+                                alloc,
+                                parens,
+                                input_type,
+                                Prec::Term,
+                            ))
                             .append(")")
                     }),
                     alloc.space(),
@@ -74,7 +127,7 @@ where
                 .group()
                 .append(
                     (alloc.nil()).append(alloc.space()).append(
-                        from_term_prec(alloc, output_type, Prec::Arrow)
+                        from_term_prec_with_parens(alloc, parens, output_type, Prec::Arrow)
                             .group()
                             .nest(4),
                     ),
@@ -84,20 +137,47 @@ where
             alloc,
             prec > Prec::Arrow,
             (alloc.nil())
-                .append(from_term_prec(alloc, input_type, Prec::App))
+                .append(from_term_prec_with_parens(
+                    alloc,
+                    parens,
+                    input_type,
+                    Prec::App,
+                ))
                 .append(alloc.space())
                 .append("->")
                 .append(alloc.space())
-                .append(from_term_prec(alloc, output_type, Prec::Arrow)),
+                .append(from_term_prec_with_parens(
+                    alloc,
+                    parens,
+                    output_type,
+                    Prec::Arrow,
+                )),
         ),
-        TermData::FunctionTerm(input_names, output_term) => paren(
+        TermData::FunctionTerm(inputs, output_term) => paren(
             alloc,
             prec > Prec::Expr,
             (alloc.nil())
                 .append("fun")
                 .append(alloc.space())
                 .append(alloc.intersperse(
-                    input_names.iter().map(|input_name| &input_name.data),
+                    inputs.iter().map(|(input_name, input_type)| {
+                        match input_type {
+                            None => alloc.text(&input_name.data),
+                            Some(input_type) => (alloc.nil())
+                                .append("(")
+                                .append(&input_name.data)
+                                .append(alloc.space())
+                                .append(":")
+                                .append(alloc.space())
+                                .append(from_term_prec_with_parens(
+                                    alloc,
+                                    parens,
+                                    input_type,
+                                    Prec::Term,
+                                ))
+                                .append(")"),
+                        }
+                    }),
                     alloc.space(),
                 ))
                 .append(alloc.space())
@@ -105,7 +185,7 @@ where
                 .group()
                 .append(
                     (alloc.nil()).append(alloc.space()).append(
-                        from_term_prec(alloc, output_term, Prec::Expr)
+                        from_term_prec_with_parens(alloc, parens, output_term, Prec::Expr)
                             .group()
                             .nest(4),
                     ),
@@ -114,12 +194,15 @@ where
         TermData::FunctionElim(head_term, input_terms) => paren(
             alloc,
             prec > Prec::App,
-            from_term_prec(alloc, head_term, Prec::App).append(
+            from_term_prec_with_parens(alloc, parens, head_term, Prec::App).append(
                 (alloc.nil())
                     .append(alloc.concat(input_terms.iter().map(|input_term| {
-                        alloc
-                            .space()
-                            .append(from_term_prec(alloc, input_term, Prec::Arrow))
+                        alloc.space().append(from_term_prec_with_parens(
+                            alloc,
+                            parens,
+                            input_term,
+                            Prec::Arrow,
+                        ))
                     })))
                     .group()
                     .nest(4),
@@ -149,7 +232,12 @@ where
                         .group()
                         .append(
                             (alloc.space())
-                                .append(from_term_prec(alloc, entry_type, Prec::Term))
+                                .append(from_term_prec_with_parens(
+                                    alloc,
+                                    parens,
+                                    entry_type,
+                                    Prec::Term,
+                                ))
                                 .append(",")
                                 .group()
                                 .nest(4),
@@ -182,7 +270,12 @@ where
                         .group()
                         .append(
                             (alloc.space())
-                                .append(from_term_prec(alloc, entry_term, Prec::Term))
+                                .append(from_term_prec_with_parens(
+                                    alloc,
+                                    parens,
+                                    entry_term,
+                                    Prec::Term,
+                                ))
                                 .append(",")
                                 .group()
                                 .nest(4),
@@ -193,27 +286,35 @@ where
             )
             .append("}"),
         TermData::RecordElim(head_term, label) => (alloc.nil())
-            .append(from_term_prec(alloc, head_term, Prec::Atomic))
+            .append(from_term_prec_with_parens(
+                alloc,
+                parens,
+                head_term,
+                Prec::Atomic,
+            ))
             .append(".")
             .append(&label.data),
 
         TermData::SequenceTerm(term_entries) => (alloc.nil())
             .append("[")
             .group()
-            .append(
-                alloc.intersperse(
-                    term_entries
-                        .iter()
-                        .map(|term| from_term_prec(alloc, term, Prec::Term).group().nest(4)),
-                    alloc.text(",").append(alloc.space()),
-                ),
-            )
+            .append(alloc.intersperse(
+                term_entries.iter().map(|term| {
+                    from_term_prec_with_parens(alloc, parens, term, Prec::Term)
+                        .group()
+                        .nest(4)
+                }),
+                alloc.text(",").append(alloc.space()),
+            ))
             .append("]"),
 
         TermData::CharTerm(text) | TermData::StringTerm(text) | TermData::NumberTerm(text) => {
             alloc.text(text)
         }
 
+        TermData::Hole(None) => alloc.text("?"),
+        TermData::Hole(Some(name)) => alloc.text("?").append(name),
+
         TermData::Error => alloc.text("!"),
     }
 }
@@ -229,3 +330,39 @@ where
         doc
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_to_pretty(parens: Parens, source: &str) -> String {
+        let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+        let term = Term::from_str(0, source, &messages_tx);
+        assert_eq!(messages_rx.try_iter().count(), 0, "errors in `{}`", source);
+
+        let pretty_alloc = pretty::BoxAllocator;
+        let doc = from_term_with_parens(&pretty_alloc, parens, &term).1;
+        let output = doc.pretty(usize::MAX).to_string();
+        output
+    }
+
+    #[test]
+    fn minimal_parens() {
+        assert_eq!(parse_to_pretty(Parens::Minimal, "((x))"), "x");
+        assert_eq!(parse_to_pretty(Parens::Minimal, "(f x) (y)"), "f x y");
+        assert_eq!(
+            parse_to_pretty(Parens::Minimal, "(A -> B) -> C"),
+            "(A -> B) -> C"
+        );
+    }
+
+    #[test]
+    fn preserve_parens() {
+        assert_eq!(parse_to_pretty(Parens::Preserve, "((x))"), "((x))");
+        assert_eq!(parse_to_pretty(Parens::Preserve, "(f x) (y)"), "(f x) (y)");
+        assert_eq!(
+            parse_to_pretty(Parens::Preserve, "(A -> B) -> C"),
+            "(A -> B) -> C"
+        );
+    }
+}
@@ -3,7 +3,8 @@
 use codespan_reporting::diagnostic::{Diagnostic, Label};
 use pretty::DocAllocator;
 
-use crate::lang::{core, surface, FileId, Location};
+use crate::lang::core::typing::Premise;
+use crate::lang::{core, surface, FileId, Located, Location};
 use crate::literal;
 
 /// Global diagnostic messages
@@ -107,6 +108,7 @@ impl Message {
 #[derive(Debug, Clone)]
 pub enum LexerError {
     InvalidToken { location: Location },
+    ReservedWord { location: Location, word: String },
 }
 
 impl LexerError {
@@ -115,6 +117,11 @@ impl LexerError {
             LexerError::InvalidToken { location } => Diagnostic::error()
                 .with_message("invalid token")
                 .with_labels(option_to_vec(primary(location))),
+            LexerError::ReservedWord { location, word } => Diagnostic::error()
+                .with_message(format!("`{}` is a reserved word", word))
+                .with_labels(option_to_vec(primary(location).map(|label| {
+                    label.with_message("reserved words cannot be used as names")
+                }))),
         }
     }
 }
@@ -318,6 +325,11 @@ impl AmbiguousTerm {
     }
 }
 
+/// The types that numeric literals can be elements of.
+const NUMERIC_TYPES: &[&str] = &[
+    "U8", "U16", "U32", "U64", "S8", "S16", "S32", "S64", "F32", "F64",
+];
+
 #[derive(Clone, Debug)]
 pub enum ExpectedType<T> {
     Universe,
@@ -330,7 +342,12 @@ pub enum CoreTypingMessage {
     UnboundGlobal {
         name: String,
     },
-    UnboundLocal,
+    UnboundLocal {
+        location: Location,
+        local_index: core::LocalIndex,
+        /// The premise that the local was found in, if premises were traced.
+        premise: Option<Premise>,
+    },
     InvalidRecordType {
         duplicate_labels: Vec<String>,
     },
@@ -360,6 +377,12 @@ pub enum CoreTypingMessage {
     MismatchedTypes {
         found_type: core::Term,
         expected_type: ExpectedType<core::Term>,
+        /// The premise that the mismatch was found in, if premises were traced.
+        premise: Option<Premise>,
+    },
+    RecursionLimitExceeded {
+        location: Location,
+        limit: usize,
     },
 }
 
@@ -379,9 +402,16 @@ impl CoreTypingMessage {
             CoreTypingMessage::UnboundGlobal { name } => {
                 Diagnostic::bug().with_message(format!("unbound global variable `{}`", name))
             }
-            CoreTypingMessage::UnboundLocal => {
-                Diagnostic::bug().with_message("unbound local variable")
-            }
+            CoreTypingMessage::UnboundLocal {
+                location,
+                local_index,
+                premise,
+            } => Diagnostic::bug()
+                .with_message("unbound local variable")
+                .with_labels(option_to_vec(primary(location).map(|label| {
+                    label.with_message(format!("local index `{}` is out of scope", local_index.0))
+                })))
+                .with_notes(premise_note(premise)),
             CoreTypingMessage::InvalidRecordType { duplicate_labels } => Diagnostic::bug()
                 .with_message("invalid record type")
                 .with_notes(
@@ -463,19 +493,30 @@ impl CoreTypingMessage {
             CoreTypingMessage::MismatchedTypes {
                 found_type,
                 expected_type,
+                premise,
             } => Diagnostic::bug()
                 .with_message("mismatched types")
-                .with_notes(vec![match expected_type {
-                    ExpectedType::Universe => format!(
-                        "expected a type, found `{}`",
-                        to_doc(&found_type).pretty(std::usize::MAX),
-                    ),
-                    ExpectedType::Type(expected_type) => format!(
-                        "expected `{}`, found `{}`",
-                        to_doc(&expected_type).pretty(std::usize::MAX),
-                        to_doc(&found_type).pretty(std::usize::MAX),
-                    ),
-                }]),
+                .with_notes(
+                    std::iter::once(match expected_type {
+                        ExpectedType::Universe => format!(
+                            "expected a type, found `{}`",
+                            to_doc(&found_type).pretty(std::usize::MAX),
+                        ),
+                        ExpectedType::Type(expected_type) => format!(
+                            "expected `{}`, found `{}`",
+                            to_doc(&expected_type).pretty(std::usize::MAX),
+                            to_doc(&found_type).pretty(std::usize::MAX),
+                        ),
+                    })
+                    .chain(premise_note(premise))
+                    .collect(),
+                ),
+            CoreTypingMessage::RecursionLimitExceeded { location, limit } => Diagnostic::error()
+                .with_message("recursion limit exceeded")
+                .with_labels(option_to_vec(primary(location).map(|label| {
+                    label.with_message("term is nested too deeply to type check")
+                })))
+                .with_notes(vec![format!("the recursion limit is {}", limit)]),
         }
     }
 }
@@ -486,12 +527,19 @@ pub enum SurfaceToCoreMessage {
     UnboundName {
         location: Location,
         name: String,
+        similar_name: Option<Located<String>>,
+    },
+    ShadowedName {
+        name: String,
+        outer_location: Location,
+        inner_location: Location,
     },
     InvalidRecordType {
         duplicate_labels: Vec<(String, Location, Location)>,
     },
     InvalidRecordTerm {
         location: Location,
+        duplicate_labels: Vec<(String, Location, Location)>,
         missing_labels: Vec<String>,
         unexpected_labels: Vec<Location>,
     },
@@ -507,6 +555,7 @@ pub enum SurfaceToCoreMessage {
     TooManyInputsInFunctionElim {
         head_location: Location,
         head_type: surface::Term,
+        input_count: usize,
         unexpected_input_terms: Vec<Location>,
     },
     NoLiteralConversion {
@@ -526,11 +575,46 @@ pub enum SurfaceToCoreMessage {
         location: Location,
         term: AmbiguousTerm,
     },
+    Hole {
+        location: Location,
+        name: Option<String>,
+        expected_type: Option<surface::Term>,
+    },
+    AnnotationNotAType {
+        location: Location,
+        found_type: surface::Term,
+    },
+    FunctionInputNotAType {
+        location: Location,
+        found_type: surface::Term,
+    },
+    FunctionOutputNotAType {
+        location: Location,
+        found_type: surface::Term,
+    },
     MismatchedTypes {
         location: Location,
         found_type: surface::Term,
         expected_type: ExpectedType<surface::Term>,
     },
+    MismatchedInputAnnotation {
+        location: Location,
+        found_type: surface::Term,
+        expected_type: surface::Term,
+    },
+    RecursionLimitExceeded {
+        location: Location,
+        limit: usize,
+    },
+    IgnoredFunctionInputs {
+        location: Location,
+        label: String,
+    },
+    InputsAppliedToType {
+        head_location: Location,
+        head_term: surface::Term,
+        unexpected_input_terms: Vec<Location>,
+    },
 }
 
 impl SurfaceToCoreMessage {
@@ -546,12 +630,47 @@ impl SurfaceToCoreMessage {
         let to_doc = |term| surface_to_pretty::from_term(pretty_alloc, term).1;
 
         match self {
-            SurfaceToCoreMessage::UnboundName { location, name } => Diagnostic::error()
+            SurfaceToCoreMessage::UnboundName {
+                location,
+                name,
+                similar_name,
+            } => Diagnostic::error()
                 .with_message(format!("cannot find `{}` in this scope", name))
-                // TODO: name suggestions?
-                .with_labels(option_to_vec(
-                    primary(location).map(|label| label.with_message("not found in this scope")),
-                )),
+                .with_labels(
+                    primary(location)
+                        .map(|label| label.with_message("not found in this scope"))
+                        .into_iter()
+                        .chain(similar_name.iter().flat_map(|similar_name| {
+                            secondary(&similar_name.location).map(|label| {
+                                label.with_message(format!(
+                                    "`{}` is defined here",
+                                    similar_name.data,
+                                ))
+                            })
+                        }))
+                        .collect(),
+                )
+                .with_notes(
+                    (similar_name.iter())
+                        .map(|similar_name| format!("help: did you mean `{}`?", similar_name.data))
+                        .collect(),
+                ),
+
+            SurfaceToCoreMessage::ShadowedName {
+                name,
+                outer_location,
+                inner_location,
+            } => Diagnostic::error()
+                .with_message(format!("`{}` is already bound in this scope", name))
+                .with_labels(
+                    primary(inner_location)
+                        .map(|label| label.with_message("shadows an existing binding"))
+                        .into_iter()
+                        .chain(secondary(outer_location).map(|label| {
+                            label.with_message(format!("`{}` is first bound here", name))
+                        }))
+                        .collect(),
+                ),
 
             SurfaceToCoreMessage::InvalidRecordType { duplicate_labels } => Diagnostic::error()
                 .with_message("invalid record type")
@@ -573,15 +692,28 @@ impl SurfaceToCoreMessage {
 
             SurfaceToCoreMessage::InvalidRecordTerm {
                 location,
+                duplicate_labels,
                 missing_labels,
                 unexpected_labels,
             } => Diagnostic::error()
                 .with_message("invalid record term")
                 .with_labels({
                     let mut labels = Vec::with_capacity(
-                        unexpected_labels.len() + if missing_labels.is_empty() { 0 } else { 1 },
+                        duplicate_labels.len() * 2
+                            + unexpected_labels.len()
+                            + if missing_labels.is_empty() { 0 } else { 1 },
                     );
 
+                    for (label_name, label_location1, label_location2) in duplicate_labels {
+                        labels.extend(secondary(label_location1).map(|label| {
+                            label.with_message(format!("first use of `{}`", label_name))
+                        }));
+                        labels
+                            .extend(primary(label_location2).map(|label| {
+                                label.with_message("entry label used more than once")
+                            }));
+                    }
+
                     for label_location in unexpected_labels {
                         labels.extend(
                             primary(label_location)
@@ -646,6 +778,7 @@ impl SurfaceToCoreMessage {
             SurfaceToCoreMessage::TooManyInputsInFunctionElim {
                 head_location,
                 head_type,
+                input_count,
                 unexpected_input_terms,
             } => Diagnostic::error()
                 .with_message("term was applied to too many inputs")
@@ -664,6 +797,39 @@ impl SurfaceToCoreMessage {
                                 .map(|label| label.with_message("unexpected input".to_owned()))
                         }))
                         .collect(),
+                )
+                .with_notes(vec![{
+                    let expected_count = input_count - unexpected_input_terms.len();
+                    format!(
+                        "expected at most {} {}, found {}",
+                        expected_count,
+                        if expected_count == 1 {
+                            "input"
+                        } else {
+                            "inputs"
+                        },
+                        input_count,
+                    )
+                }]),
+
+            SurfaceToCoreMessage::InputsAppliedToType {
+                head_location,
+                head_term,
+                unexpected_input_terms,
+            } => Diagnostic::error()
+                .with_message(format!(
+                    "`{}` is not a function",
+                    to_doc(head_term).pretty(usize::MAX),
+                ))
+                .with_labels(
+                    primary(head_location)
+                        .map(|label| label.with_message("expected a function, found a type"))
+                        .into_iter()
+                        .chain(unexpected_input_terms.iter().flat_map(|input_location| {
+                            primary(input_location)
+                                .map(|label| label.with_message("unexpected input".to_owned()))
+                        }))
+                        .collect(),
                 ),
 
             SurfaceToCoreMessage::NoLiteralConversion {
@@ -711,7 +877,78 @@ impl SurfaceToCoreMessage {
                 .with_message(format!("ambiguous {}", term.description()))
                 .with_labels(option_to_vec(
                     primary(location).map(|label| label.with_message("type annotations needed")),
-                )),
+                ))
+                .with_notes(match term {
+                    AmbiguousTerm::NumberLiteral => {
+                        vec![format!(
+                        "numeric literals can be annotated with one of the types {}, eg. `1 : S32`",
+                        NUMERIC_TYPES.iter().map(|name| format!("`{}`", name)).format(", "),
+                    )]
+                    }
+                    _ => Vec::new(),
+                }),
+
+            SurfaceToCoreMessage::Hole {
+                location,
+                name,
+                expected_type,
+            } => Diagnostic::error()
+                .with_message(match name {
+                    Some(name) => format!("found hole `?{}`", name),
+                    None => "found hole".to_owned(),
+                })
+                .with_labels(option_to_vec(primary(location).map(|label| {
+                    label.with_message(match (name, expected_type) {
+                        (Some(name), Some(expected_type)) => format!(
+                            "goal `{}` : `{}`",
+                            name,
+                            to_doc(expected_type).pretty(usize::MAX),
+                        ),
+                        (None, Some(expected_type)) => {
+                            format!("goal : `{}`", to_doc(expected_type).pretty(usize::MAX))
+                        }
+                        (_, None) => "type annotations needed".to_owned(),
+                    })
+                }))),
+
+            SurfaceToCoreMessage::AnnotationNotAType {
+                location,
+                found_type,
+            } => Diagnostic::error()
+                .with_message("annotation is not a type")
+                .with_labels(option_to_vec(primary(location).map(|label| {
+                    label.with_message(format!(
+                        // TODO: multi-line?
+                        "expected a type, found a term of type `{}`",
+                        to_doc(found_type).pretty(usize::MAX),
+                    ))
+                }))),
+
+            SurfaceToCoreMessage::FunctionInputNotAType {
+                location,
+                found_type,
+            } => Diagnostic::error()
+                .with_message("function input is not a type")
+                .with_labels(option_to_vec(primary(location).map(|label| {
+                    label.with_message(format!(
+                        // TODO: multi-line?
+                        "expected a type, found a term of type `{}`",
+                        to_doc(found_type).pretty(usize::MAX),
+                    ))
+                }))),
+
+            SurfaceToCoreMessage::FunctionOutputNotAType {
+                location,
+                found_type,
+            } => Diagnostic::error()
+                .with_message("function output is not a type")
+                .with_labels(option_to_vec(primary(location).map(|label| {
+                    label.with_message(format!(
+                        // TODO: multi-line?
+                        "expected a type, found a term of type `{}`",
+                        to_doc(found_type).pretty(usize::MAX),
+                    ))
+                }))),
 
             SurfaceToCoreMessage::MismatchedTypes {
                 location,
@@ -734,6 +971,41 @@ impl SurfaceToCoreMessage {
                         ),
                     })
                 }))),
+            SurfaceToCoreMessage::MismatchedInputAnnotation {
+                location,
+                found_type,
+                expected_type,
+            } => Diagnostic::error()
+                .with_message("mismatched input annotation")
+                .with_labels(option_to_vec(primary(location).map(|label| {
+                    label.with_message(format!(
+                        "expected `{}`, found `{}`",
+                        to_doc(expected_type).pretty(usize::MAX),
+                        to_doc(found_type).pretty(usize::MAX),
+                    ))
+                })))
+                .with_notes(vec![
+                    "the annotation must match the input type of the expected function type"
+                        .to_owned(),
+                ]),
+
+            SurfaceToCoreMessage::RecursionLimitExceeded { location, limit } => Diagnostic::error()
+                .with_message("recursion limit exceeded")
+                .with_labels(option_to_vec(primary(location).map(|label| {
+                    label.with_message("term is nested too deeply to elaborate")
+                })))
+                .with_notes(vec![format!("the recursion limit is {}", limit)]),
+            SurfaceToCoreMessage::IgnoredFunctionInputs { location, label } => {
+                Diagnostic::warning()
+                    .with_message(format!("`{}` ignores all of its inputs", label))
+                    .with_labels(option_to_vec(
+                        primary(location).map(|label| label.with_message("inputs are never used")),
+                    ))
+                    .with_notes(vec![
+                        "if this is intentional, prefix the input names with an underscore"
+                            .to_owned(),
+                    ])
+            }
         }
     }
 }
@@ -758,6 +1030,14 @@ fn secondary(location: &Location) -> Option<Label<FileId>> {
     }
 }
 
+/// Describe the premise of the typing rule that an internal error was found
+/// in, if it is known.
+fn premise_note(premise: &Option<Premise>) -> Vec<String> {
+    (premise.iter())
+        .map(|premise| format!("while applying the rule `{}`", premise))
+        .collect()
+}
+
 fn option_to_vec<T>(option: Option<T>) -> Vec<T> {
     match option {
         None => Vec::new(),
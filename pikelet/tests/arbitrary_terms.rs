@@ -0,0 +1,116 @@
+//! Property tests that check that the elaborator does not panic when given
+//! arbitrary surface terms.
+
+use pikelet::lang::core::semantics::Value;
+use pikelet::lang::surface::{Term, TermData, TermEntry};
+use pikelet::lang::{core, Located};
+use pikelet::pass::surface_to_core;
+use proptest::prelude::*;
+use proptest::sample::select;
+use std::sync::Arc;
+
+fn arb_name() -> impl Strategy<Value = String> {
+    select(vec![
+        "x", "y", "A", "Type", "Bool", "true", "U32", "List", "Array", "unknown",
+    ])
+    .prop_map(str::to_owned)
+}
+
+fn arb_located_name() -> impl Strategy<Value = Located<String>> {
+    arb_name().prop_map(Located::generated)
+}
+
+fn arb_entry(term: impl Strategy<Value = Term>) -> impl Strategy<Value = TermEntry> {
+    (
+        arb_located_name(),
+        prop::option::of(arb_located_name()),
+        term,
+    )
+}
+
+fn arb_term() -> impl Strategy<Value = Term> {
+    let leaf = prop_oneof![
+        arb_name().prop_map(TermData::Name),
+        prop::option::of(arb_name()).prop_map(TermData::Hole),
+        select(vec![
+            "0", "1", "-1", "256", "1.5", "0x1F", "1_000", "1e3", "-"
+        ])
+        .prop_map(|data| TermData::NumberTerm(data.to_owned())),
+        select(vec![
+            "'a'",
+            "'ab'",
+            "''",
+            "'\\n'",
+            "'\\u{1F600}'",
+            "'\\x7F'"
+        ])
+        .prop_map(|data| TermData::CharTerm(data.to_owned())),
+        select(vec!["\"\"", "\"hello\"", "\"\\q\"", "\"\\u{}\""])
+            .prop_map(|data| TermData::StringTerm(data.to_owned())),
+        Just(TermData::Error),
+    ]
+    .prop_map(Term::generated);
+
+    leaf.prop_recursive(6, 64, 4, |term| {
+        prop_oneof![
+            (term.clone(), term.clone())
+                .prop_map(|(term, r#type)| TermData::Ann(Box::new(term), Box::new(r#type))),
+            (term.clone(), term.clone()).prop_map(|(input_type, output_type)| {
+                TermData::FunctionArrowType(Box::new(input_type), Box::new(output_type))
+            }),
+            (
+                prop::collection::vec(
+                    (
+                        prop::collection::vec(arb_located_name(), 1..3),
+                        term.clone()
+                    ),
+                    1..3,
+                ),
+                term.clone(),
+            )
+                .prop_map(|(input_type_groups, output_type)| {
+                    TermData::FunctionType(input_type_groups, Box::new(output_type))
+                }),
+            (
+                prop::collection::vec((arb_located_name(), prop::option::of(term.clone())), 1..3),
+                term.clone()
+            )
+                .prop_map(|(inputs, output_term)| {
+                    TermData::FunctionTerm(inputs, Box::new(output_term))
+                }),
+            (term.clone(), prop::collection::vec(term.clone(), 1..3)).prop_map(
+                |(head_term, input_terms)| TermData::FunctionElim(Box::new(head_term), input_terms)
+            ),
+            prop::collection::vec(arb_entry(term.clone()), 0..3).prop_map(TermData::RecordType),
+            prop::collection::vec(arb_entry(term.clone()), 0..3).prop_map(TermData::RecordTerm),
+            (term.clone(), arb_located_name())
+                .prop_map(|(head_term, label)| TermData::RecordElim(Box::new(head_term), label)),
+            prop::collection::vec(term, 0..3).prop_map(TermData::SequenceTerm),
+        ]
+        .prop_map(Term::generated)
+    })
+}
+
+proptest! {
+    #[test]
+    fn elaboration_does_not_panic(term in arb_term()) {
+        let globals = core::Globals::default();
+        let pretty_alloc = pretty::BoxAllocator;
+        let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+
+        let mut state = surface_to_core::State::new(&globals, messages_tx.clone());
+        let (core_term, r#type) = state.synth_type(&term);
+        state.check_type(&term, &Arc::new(Value::TypeType));
+        if messages_rx.is_empty() {
+            state.normalize(&core_term);
+        }
+
+        let mut state = core::typing::State::new(&globals, messages_tx);
+        state.synth_type(&core_term);
+        state.check_type(&core_term, &r#type);
+
+        for message in messages_rx.try_iter() {
+            message.to_diagnostic(&pretty_alloc);
+        }
+    }
+}
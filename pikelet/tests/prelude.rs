@@ -0,0 +1,270 @@
+//! Tests of the definitions in the prelude.
+
+use fxhash::FxHashMap;
+use pikelet::lang::{core, surface};
+use pikelet::library::{self, PRELUDE};
+use pikelet::pass::surface_to_core;
+use std::sync::Arc;
+
+/// Extract the examples from the doc comments in a source file.
+///
+/// Examples are written as a line starting with `>>>`, followed by the term
+/// to evaluate, with the expected result on the following line:
+///
+/// ```text
+/// ||| >>> id Type Bool
+/// ||| Bool
+/// ```
+fn extract_examples(source: &str) -> Vec<(usize, String, String)> {
+    let doc_lines = source.lines().enumerate().map(|(index, line)| {
+        let doc_line = line.trim_start().strip_prefix("|||").map(str::trim);
+        (index + 1, doc_line)
+    });
+
+    let mut examples = Vec::new();
+    let mut pending_input = None;
+
+    for (line_number, doc_line) in doc_lines {
+        match (pending_input.take(), doc_line) {
+            (None, Some(doc_line)) => {
+                if let Some(input) = doc_line.strip_prefix(">>>") {
+                    pending_input = Some((line_number, input.trim().to_owned()));
+                }
+            }
+            (Some((line_number, input)), Some(expected)) if !expected.is_empty() => {
+                examples.push((line_number, input, expected.to_owned()));
+            }
+            (Some((line_number, _)), _) => {
+                panic!(
+                    "missing expected result for example on line {}",
+                    line_number
+                )
+            }
+            (None, None) => {}
+        }
+    }
+
+    examples
+}
+
+/// The elaborated prelude, along with its type.
+fn prelude_and_type(
+    state: &mut surface_to_core::State<'_>,
+) -> (&'static core::Term, Arc<core::semantics::Value>) {
+    let core_prelude = library::prelude_core().expect("errors in the prelude");
+    match &core_prelude.data {
+        core::TermData::Ann(_, r#type) => (core_prelude, state.eval(r#type)),
+        _ => panic!("expected the prelude to be annotated"),
+    }
+}
+
+/// Add each of the entries of the prelude to the globals.
+fn prelude_globals() -> core::Globals {
+    let globals = core::Globals::default();
+    let (messages_tx, _) = crossbeam_channel::unbounded();
+
+    let mut state = surface_to_core::State::new(&globals, messages_tx);
+    let (core_prelude, prelude_type) = prelude_and_type(&mut state);
+
+    let core_prelude = Arc::new(core_prelude.clone());
+    let mut entries = (globals.entries())
+        .map(|(name, entry)| (name.clone(), entry.clone()))
+        .collect::<FxHashMap<_, _>>();
+
+    if let core::semantics::Value::RecordType(labels, _) = prelude_type.as_ref() {
+        for label in labels.iter() {
+            let entry_type = state.record_elim_type(&core_prelude, &prelude_type, label);
+            let entry_type = state.read_back(&entry_type.unwrap());
+            let entry_term = core::TermData::RecordElim(core_prelude.clone(), label.clone());
+            entries.insert(
+                label.clone(),
+                (
+                    Arc::new(entry_type),
+                    Some(Arc::new(core::Term::generated(entry_term))),
+                ),
+            );
+        }
+    }
+
+    core::Globals::new(entries)
+}
+
+/// Check that the entries of the prelude have the expected types.
+fn check_prelude_entries(expected_entries: &[(&str, &str)]) {
+    let globals = core::Globals::default();
+    let pretty_alloc = pretty::BoxAllocator;
+    let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+
+    let mut state = surface_to_core::State::new(&globals, messages_tx.clone());
+    let (core_prelude, prelude_type) = prelude_and_type(&mut state);
+
+    for (label, expected_type) in expected_entries {
+        let entry_type = (state.record_elim_type(&core_prelude, &prelude_type, label))
+            .unwrap_or_else(|| panic!("no entry `{}` in the prelude", label));
+
+        let expected_type = surface::Term::from_str(1, expected_type, &messages_tx);
+        let expected_type = state.is_type(&expected_type).unwrap();
+        let expected_type = state.eval(&expected_type);
+        assert_eq!(messages_rx.try_iter().count(), 0, "errors in `{}`", label);
+
+        if !state.is_equal(&entry_type, &expected_type) {
+            let entry_type = state.read_back_to_surface(&entry_type);
+            panic!(
+                "unexpected type for `{}`: {}",
+                label,
+                surface_to_pretty(&pretty_alloc, &entry_type),
+            );
+        }
+    }
+}
+
+fn surface_to_pretty(pretty_alloc: &pretty::BoxAllocator, term: &surface::Term) -> String {
+    pikelet::pass::surface_to_pretty::from_term(pretty_alloc, term)
+        .1
+        .pretty(usize::MAX)
+        .to_string()
+}
+
+#[test]
+fn combinators() {
+    check_prelude_entries(&[
+        ("id", "Fun (A : Type) -> A -> A"),
+        ("the", "Fun (A : Type) -> A -> A"),
+        ("always", "Fun (A B : Type) -> A -> B -> A"),
+        ("const", "Fun (A B : Type) -> A -> B -> A"),
+        ("apply", "Fun (A B : Type) -> (A -> B) -> A -> B"),
+        ("pipe", "Fun (A B : Type) -> A -> (A -> B) -> B"),
+        (
+            "compose",
+            "Fun (A B C : Type) -> (A -> B) -> (B -> C) -> A -> C",
+        ),
+        ("flip", "Fun (A B C : Type) -> (A -> B -> C) -> B -> A -> C"),
+        (
+            "subst",
+            "Fun (A B C : Type) -> (A -> B -> C) -> (A -> B) -> A -> C",
+        ),
+    ]);
+}
+
+#[test]
+fn dependent_combinators() {
+    check_prelude_entries(&[
+        (
+            "dep-apply",
+            "Fun (A : Type) (B : A -> Type) (a-b : Fun (a : A) -> B a) (a : A) -> B a",
+        ),
+        (
+            "dep-flip",
+            "Fun (A B : Type) (C : A -> B -> Type) (a-b-c : Fun (a : A) (b : B) -> C a b) \
+                (b : B) (a : A) -> C a b",
+        ),
+    ]);
+}
+
+#[test]
+fn boolean_combinators() {
+    const CHURCH_BOOL: &str = "(Fun (A : Type) -> A -> A -> A)";
+
+    let unary = format!("{0} -> {0}", CHURCH_BOOL);
+    let binary = format!("{0} -> {0} -> {0}", CHURCH_BOOL);
+    let to_bool = format!("{} -> Bool", CHURCH_BOOL);
+
+    check_prelude_entries(&[
+        ("Church-Bool", "Type"),
+        ("church-true", CHURCH_BOOL),
+        ("church-false", CHURCH_BOOL),
+        ("to-bool", &to_bool),
+        ("not", &unary),
+        ("and", &binary),
+        ("or", &binary),
+    ]);
+}
+
+#[test]
+fn types() {
+    check_prelude_entries(&[
+        ("Unit", "Type"),
+        ("Prod", "Fun (A : Type) (B : A -> Type) -> Type"),
+        ("Sum", "Fun (A : Type) (B : A -> Type) -> Type"),
+        ("Semigroup", "Type"),
+        ("Category", "Type"),
+    ]);
+}
+
+#[test]
+fn doc_examples() {
+    let globals = prelude_globals();
+    let examples = extract_examples(PRELUDE);
+    assert!(!examples.is_empty(), "no examples found in the prelude");
+
+    for (line_number, input, expected) in examples {
+        let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+        let mut state = surface_to_core::State::new(&globals, messages_tx.clone());
+
+        let input_term = surface::Term::from_str(0, &input, &messages_tx);
+        let (core_input_term, _) = state.synth_type(&input_term);
+        let input_value = state.eval(&core_input_term);
+
+        let expected_term = surface::Term::from_str(1, &expected, &messages_tx);
+        let (core_expected_term, _) = state.synth_type(&expected_term);
+        let expected_value = state.eval(&core_expected_term);
+
+        assert_eq!(
+            messages_rx.try_iter().count(),
+            0,
+            "errors in the example on line {}",
+            line_number,
+        );
+        assert!(
+            state.is_equal(&input_value, &expected_value),
+            "example on line {}: expected `{}` to evaluate to `{}`",
+            line_number,
+            input,
+            expected,
+        );
+    }
+}
+
+#[test]
+fn program_using_prelude() {
+    const PROGRAM: &str = r#"
+        record {
+            twice = fun A f => compose A A A f f,
+            List2 = twice Type List,
+            main = id Type (List2 Bool),
+        } : Record {
+            twice : Fun (A : Type) -> (A -> A) -> A -> A,
+            List2 : Type -> Type,
+            main : Type,
+        }
+    "#;
+
+    let globals = prelude_globals();
+    let (messages_tx, messages_rx) = crossbeam_channel::unbounded();
+    let mut state = surface_to_core::State::new(&globals, messages_tx.clone());
+
+    let program = surface::Term::from_str(0, PROGRAM, &messages_tx);
+    let (core_program, program_type) = state.synth_type(&program);
+    assert_eq!(messages_rx.try_iter().count(), 0, "errors in the program");
+
+    let core_program = Arc::new(core_program);
+    let main_type = state.record_elim_type(&core_program, &program_type, "main");
+    assert!(matches!(
+        main_type.unwrap().force(&globals),
+        core::semantics::Value::TypeType,
+    ));
+
+    let main_term =
+        core::Term::generated(core::TermData::RecordElim(core_program, "main".to_owned()));
+    let main_value = state.eval(&main_term);
+    let expected_term = surface::Term::from_str(1, "List (List Bool)", &messages_tx);
+    let expected_term = state.is_type(&expected_term).unwrap();
+    let expected_value = state.eval(&expected_term);
+    assert_eq!(messages_rx.try_iter().count(), 0);
+
+    assert!(
+        state.is_equal(&main_value, &expected_value),
+        "unexpected value for `main`: {}",
+        state.normalize(&main_term),
+    );
+}